@@ -1,24 +1,123 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use crate::config::{Signature, SignatureRole};
+use crate::diff::{normalize_path, StagingArea, StagingEntry};
+use crate::ignore::IgnoreRules;
+use crate::lockfile::write_atomic;
+use crate::object::{decode_object, encode_object, object_hash, ObjectType};
+use crate::pack::Pack;
+use crate::refs::{is_full_hash, Head};
+
 /// Estrutura principal do repositório COGIT
 pub struct CogitRepository {
     root_path: PathBuf,
     cogit_dir: PathBuf,
+    packs: RefCell<Option<Vec<Pack>>>,  // Índices dos packs, carregados sob demanda
 }
 
 /// Representa um commit no sistema COGIT
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredCommit")]
 pub struct Commit {
-    pub hash: String,
+    #[serde(skip_serializing)]
+    pub hash: String,                  // Nome do objeto, preenchido ao carregar (não faz parte do conteúdo)
     pub message: String,
     pub timestamp: DateTime<Utc>,
-    pub parent: Option<String>,
+    pub parents: Vec<String>,          // Vazio no commit raiz, dois ou mais em um merge
     pub tree_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<Signature>,     // Quem escreveu as mudanças (ausente em commits antigos)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committer: Option<Signature>,  // Quem registrou o commit
+}
+
+/// Formato gravado de um commit: commits antigos têm um único `parent`
+#[derive(Deserialize)]
+struct StoredCommit {
+    #[serde(default)]
+    hash: String,
+    message: String,
+    timestamp: DateTime<Utc>,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    parents: Vec<String>,
+    tree_hash: String,
+    #[serde(default)]
+    author: Option<Signature>,
+    #[serde(default)]
+    committer: Option<Signature>,
+}
+
+impl From<StoredCommit> for Commit {
+    fn from(stored: StoredCommit) -> Self {
+        let mut parents = stored.parents;
+        if parents.is_empty() {
+            parents.extend(stored.parent);
+        }
+
+        Commit {
+            hash: stored.hash,
+            message: stored.message,
+            timestamp: stored.timestamp,
+            parents,
+            tree_hash: stored.tree_hash,
+            author: stored.author,
+            committer: stored.committer,
+        }
+    }
+}
+
+impl Commit {
+    /// Codificação canônica do commit, da qual o hash é calculado
+    ///
+    /// Chaves em ordem alfabética, sem espaços, datas em RFC 3339 UTC com
+    /// precisão de segundos e sem o campo `hash`: a saída depende apenas do
+    /// conteúdo, não da versão do serde nem da ordem dos campos na struct.
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, CogitError> {
+        let mut fields = Vec::new();
+        if let Some(author) = &self.author {
+            fields.push(("author", canonical_signature(author)?));
+        }
+        if let Some(committer) = &self.committer {
+            fields.push(("committer", canonical_signature(committer)?));
+        }
+        fields.push(("message", serde_json::to_string(&self.message)?));
+        fields.push(("parents", serde_json::to_string(&self.parents)?));
+        fields.push(("timestamp", canonical_timestamp(&self.timestamp)));
+        fields.push(("tree_hash", serde_json::to_string(&self.tree_hash)?));
+
+        Ok(canonical_object(&fields).into_bytes())
+    }
+}
+
+/// Data no formato canônico: RFC 3339 em UTC, precisão de segundos ("...T12:00:00Z")
+pub fn canonical_timestamp(timestamp: &DateTime<Utc>) -> String {
+    format!("\"{}\"", timestamp.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+pub fn canonical_signature(signature: &Signature) -> Result<String, CogitError> {
+    Ok(canonical_object(&[
+        ("email", serde_json::to_string(&signature.email)?),
+        ("name", serde_json::to_string(&signature.name)?),
+        ("timestamp", canonical_timestamp(&signature.timestamp)),
+    ]))
+}
+
+/// Monta um objeto JSON a partir de pares chave/valor já serializados (em ordem)
+pub fn canonical_object(fields: &[(&str, String)]) -> String {
+    let body: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("\"{}\":{}", key, value))
+        .collect();
+    format!("{{{}}}", body.join(","))
 }
 
 /// Representa uma entrada na árvore de arquivos
@@ -36,6 +135,23 @@ pub enum CogitError {
     NotARepository,
     InvalidHash,
     SerializationError(serde_json::Error),
+    BranchNotFound(String),
+    BranchExists(String),
+    InvalidRefName(String),
+    UncommittedChanges(Vec<String>),
+    UnknownRevision(String),
+    AmbiguousRevision(String, Vec<String>),
+    CorruptObject(String, String),
+    LockHeld(String),
+    InvalidConfig(String),
+    UnexpectedObjectType(String, ObjectType),
+    UnmergedPaths(Vec<String>),
+    TagNotFound(String),
+    TagExists(String),
+    OperationInProgress(String),
+    NoOperationInProgress(String),
+    MergeCommitNotSupported(String),
+    RefChanged(String),
 }
 
 impl std::fmt::Display for CogitError {
@@ -45,6 +161,50 @@ impl std::fmt::Display for CogitError {
             CogitError::NotARepository => write!(f, "Não é um repositório COGIT válido"),
             CogitError::InvalidHash => write!(f, "Hash inválido"),
             CogitError::SerializationError(e) => write!(f, "Erro de serialização: {}", e),
+            CogitError::BranchNotFound(name) => write!(f, "Branch não encontrado: {}", name),
+            CogitError::BranchExists(name) => write!(f, "Branch já existe: {}", name),
+            CogitError::InvalidRefName(name) => write!(f, "Referência inválida: {}", name),
+            CogitError::UncommittedChanges(paths) => write!(
+                f,
+                "Mudanças não commitadas seriam sobrescritas em: {} (use --force para descartá-las)",
+                paths.join(", ")
+            ),
+            CogitError::UnknownRevision(rev) => write!(f, "Revisão desconhecida: {}", rev),
+            CogitError::AmbiguousRevision(rev, candidates) => write!(
+                f,
+                "Revisão ambígua: {} (candidatos: {})",
+                rev,
+                candidates.iter().map(|hash| &hash[..12]).collect::<Vec<_>>().join(", ")
+            ),
+            CogitError::CorruptObject(hash, reason) => write!(f, "Objeto corrompido {}: {}", hash, reason),
+            CogitError::InvalidConfig(reason) => write!(f, "Configuração inválida: {}", reason),
+            CogitError::LockHeld(lock_path) => write!(
+                f,
+                "Outro processo cogit está alterando o repositório ({} existe); se nenhum estiver em execução, remova esse arquivo",
+                lock_path
+            ),
+            CogitError::UnexpectedObjectType(hash, expected) => {
+                write!(f, "Objeto {} não é do tipo {}", hash, expected)
+            }
+            CogitError::UnmergedPaths(paths) => write!(
+                f,
+                "Conflitos não resolvidos em: {} (edite os arquivos e use 'cogit add')",
+                paths.join(", ")
+            ),
+            CogitError::TagNotFound(name) => write!(f, "Tag não encontrada: {}", name),
+            CogitError::TagExists(name) => write!(f, "Tag já existe: {} (use --force para substituí-la)", name),
+            CogitError::OperationInProgress(operation) => write!(
+                f,
+                "Há um {} em andamento; conclua-o ou cancele com --abort",
+                operation
+            ),
+            CogitError::NoOperationInProgress(operation) => write!(f, "Nenhum {} em andamento", operation),
+            CogitError::RefChanged(name) => {
+                write!(f, "A referência {} foi alterada por outro processo; tente novamente", name)
+            }
+            CogitError::MergeCommitNotSupported(hash) => {
+                write!(f, "O commit {} é um merge; cherry-pick e revert de merges não são suportados", hash)
+            }
         }
     }
 }
@@ -74,7 +234,7 @@ impl CogitRepository {
         fs::create_dir_all(cogit_dir.join("refs").join("heads"))?;
 
         // Cria arquivo HEAD inicial
-        fs::write(cogit_dir.join("HEAD"), "ref: refs/heads/main\n")?;
+        write_atomic(&cogit_dir.join("HEAD"), b"ref: refs/heads/main\n")?;
 
         // Cria arquivo de configuração inicial
         let config = serde_json::json!({
@@ -82,9 +242,9 @@ impl CogitRepository {
             "created": Utc::now(),
             "description": "Repositório COGIT - Cognition Git"
         });
-        fs::write(cogit_dir.join("config.json"), serde_json::to_string_pretty(&config)?)?;
+        write_atomic(&cogit_dir.join("config.json"), serde_json::to_string_pretty(&config)?.as_bytes())?;
 
-        Ok(Self { root_path, cogit_dir })
+        Ok(Self { root_path, cogit_dir, packs: RefCell::new(None) })
     }
 
     /// Abre um repositório COGIT existente
@@ -96,7 +256,22 @@ impl CogitRepository {
             return Err(CogitError::NotARepository);
         }
 
-        Ok(Self { root_path, cogit_dir })
+        Ok(Self { root_path, cogit_dir, packs: RefCell::new(None) })
+    }
+
+    /// Diretório raiz do working tree
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Diretório interno .cogit
+    pub fn cogit_dir(&self) -> &Path {
+        &self.cogit_dir
+    }
+
+    /// Cache dos índices de packs
+    pub fn pack_cache(&self) -> &RefCell<Option<Vec<Pack>>> {
+        &self.packs
     }
 
     /// Calcula o hash SHA-256 de um conteúdo
@@ -106,108 +281,413 @@ impl CogitRepository {
         format!("{:x}", hasher.finalize())
     }
 
+    /// Nome que um arquivo com esse conteúdo teria como blob
+    pub fn blob_hash(content: &[u8]) -> String {
+        object_hash(ObjectType::Blob, content)
+    }
+
+    /// Nome com que um conteúdo é (ou seria) gravado como objeto
+    ///
+    /// Normalmente é o hash do objeto enquadrado. Se o mesmo conteúdo já existe
+    /// com o nome antigo (hash do conteúdo bruto) e com o mesmo tipo, esse nome
+    /// é reaproveitado, para que arquivos e árvores inalterados de repositórios
+    /// antigos continuem com o mesmo hash.
+    pub fn object_name(&self, object_type: ObjectType, content: &[u8]) -> Result<String, CogitError> {
+        let hash = object_hash(object_type, content);
+        if !self.has_object(&hash)? {
+            let legacy_hash = Self::calculate_hash(content);
+            if self.has_object(&legacy_hash)? && self.read_object(&legacy_hash)?.0 == object_type {
+                return Ok(legacy_hash);
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Nome do blob de um arquivo do working tree (veja `object_name`)
+    pub fn working_blob_hash(&self, content: &[u8]) -> Result<String, CogitError> {
+        self.object_name(ObjectType::Blob, content)
+    }
+
     /// Armazena um objeto no sistema content-addressable
-    fn store_object(&self, content: &[u8]) -> Result<String, CogitError> {
-        let hash = Self::calculate_hash(content);
-        let object_dir = self.cogit_dir.join("objects").join(&hash[..2]);
-        fs::create_dir_all(&object_dir)?;
-        
-        let object_path = object_dir.join(&hash[2..]);
-        fs::write(object_path, content)?;
+    ///
+    /// O hash é calculado sobre o objeto enquadrado (tipo + tamanho + conteúdo),
+    /// que é também o que fica no disco, comprimido.
+    pub fn store_object(&self, object_type: ObjectType, content: &[u8]) -> Result<String, CogitError> {
+        let hash = self.object_name(object_type, content)?;
+        if self.has_object(&hash)? {
+            return Ok(hash);
+        }
+
+        self.write_loose_object(&hash, &encode_object(object_type, content)?)?;
         
         Ok(hash)
     }
 
-    /// Cria uma árvore a partir do diretório atual
-    fn create_tree(&self) -> Result<String, CogitError> {
+    /// Grava os bytes (já codificados) de um objeto solto
+    pub fn write_loose_object(&self, hash: &str, data: &[u8]) -> Result<(), CogitError> {
+        let object_path = self.loose_object_path(hash);
+        if let Some(object_dir) = object_path.parent() {
+            fs::create_dir_all(object_dir)?;
+        }
+        write_atomic(&object_path, data)
+    }
+
+    /// Cria a árvore do próximo commit: arquivos do HEAD com as entradas do
+    /// staging area aplicadas por cima
+    pub fn create_tree(&self, staging_area: &StagingArea) -> Result<String, CogitError> {
+        let mut files = self.head_files()?;
+
+        for (path, entry) in &staging_area.entries {
+            if entry.deleted {
+                files.remove(path);
+                continue;
+            }
+            self.ensure_staged_blob(entry)?;
+            files.insert(path.clone(), entry.content_hash.clone());
+        }
+
+        self.create_tree_from_files(&files)
+    }
+
+    /// Garante que o blob de uma entrada staged está no object store
+    ///
+    /// Índices antigos registravam apenas o hash; nesse caso o conteúdo só pode
+    /// ser recuperado do working tree se ainda for idêntico ao que foi staged.
+    fn ensure_staged_blob(&self, entry: &StagingEntry) -> Result<(), CogitError> {
+        if self.has_object(&entry.content_hash)? {
+            return Ok(());
+        }
+
+        let content = fs::read(self.root_path.join(&entry.file_path)).unwrap_or_default();
+        if Self::calculate_hash(&content) == entry.content_hash {
+            // Nome antigo (hash do conteúdo bruto): o blob é gravado no formato legado
+            return self.write_loose_object(&entry.content_hash, &content);
+        }
+        if Self::blob_hash(&content) != entry.content_hash {
+            return Err(CogitError::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Conteúdo staged de '{}' não está disponível; execute 'cogit add {}' novamente",
+                    entry.file_path, entry.file_path
+                ),
+            )));
+        }
+
+        self.store_object(ObjectType::Blob, &content)?;
+        Ok(())
+    }
+
+    /// Cria recursivamente as árvores a partir de um mapa caminho relativo -> hash do blob
+    pub fn create_tree_from_files(&self, files: &BTreeMap<String, String>) -> Result<String, CogitError> {
         let mut entries = Vec::new();
-        
-        // Percorre os arquivos no diretório raiz (implementação simplificada)
-        for entry in fs::read_dir(&self.root_path)? {
+        let mut subdirs: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+
+        for (path, hash) in files {
+            match path.split_once('/') {
+                Some((dir, rest)) => {
+                    subdirs
+                        .entry(dir.to_string())
+                        .or_default()
+                        .insert(rest.to_string(), hash.clone());
+                }
+                None => entries.push(TreeEntry {
+                    name: path.clone(),
+                    hash: hash.clone(),
+                    is_file: true,
+                }),
+            }
+        }
+
+        for (name, sub_files) in subdirs {
+            let hash = self.create_tree_from_files(&sub_files)?;
+            entries.push(TreeEntry {
+                name,
+                hash,
+                is_file: false,
+            });
+        }
+
+        // Ordena para que o hash da árvore seja determinístico
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let tree_content = serde_json::to_vec(&entries)?;
+        self.store_object(ObjectType::Tree, &tree_content)
+    }
+
+    /// Achata uma árvore (e suas sub-árvores) em um mapa caminho relativo -> hash do blob
+    pub fn flatten_tree(&self, tree_hash: &str) -> Result<BTreeMap<String, String>, CogitError> {
+        let mut files = BTreeMap::new();
+        self.flatten_tree_into(tree_hash, "", &mut files)?;
+        Ok(files)
+    }
+
+    fn flatten_tree_into(
+        &self,
+        tree_hash: &str,
+        prefix: &str,
+        files: &mut BTreeMap<String, String>,
+    ) -> Result<(), CogitError> {
+        for entry in self.load_tree(tree_hash)? {
+            let path = if prefix.is_empty() {
+                entry.name
+            } else {
+                format!("{}/{}", prefix, entry.name)
+            };
+
+            if entry.is_file {
+                files.insert(path, entry.hash);
+            } else {
+                self.flatten_tree_into(&entry.hash, &path, files)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Obtém os arquivos do commit atual (HEAD), ou um mapa vazio se ainda não há commits
+    pub fn head_files(&self) -> Result<BTreeMap<String, String>, CogitError> {
+        match self.head_commit()? {
+            Some(hash) => self.flatten_tree(&self.load_commit(&hash)?.tree_hash),
+            None => Ok(BTreeMap::new()),
+        }
+    }
+
+    /// Lista recursivamente os arquivos do working tree (caminhos relativos com '/'),
+    /// respeitando as regras dos arquivos .cogitignore
+    pub fn walk_working_tree(root: &Path) -> Result<Vec<String>, CogitError> {
+        let rules = IgnoreRules::load(root)?;
+        let mut files = Vec::new();
+        Self::walk_dir(root, "", &rules, &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Verifica se um caminho não rastreado é ignorado pelas regras do .cogitignore
+    ///
+    /// Arquivos já rastreados (no HEAD ou no index) nunca são considerados
+    /// ignorados, assim como no git.
+    pub fn is_untracked_ignored(&self, path: &Path) -> Result<bool, CogitError> {
+        let relative = normalize_path(path);
+        if self.index_files()?.contains_key(&relative) {
+            return Ok(false);
+        }
+
+        let rules = IgnoreRules::load(&self.root_path)?;
+        Ok(rules.is_ignored(&relative, path.is_dir()))
+    }
+
+    fn walk_dir(dir: &Path, prefix: &str, rules: &IgnoreRules, files: &mut Vec<String>) -> Result<(), CogitError> {
+        for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
-            let name = path.file_name().unwrap().to_string_lossy().to_string();
-            
-            // Ignora o diretório .cogit e .git
-            if name.starts_with('.') {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            let relative = if prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+
+            let is_dir = path.is_dir();
+            if rules.is_ignored(&relative, is_dir) {
                 continue;
             }
-            
+
             if path.is_file() {
-                let content = fs::read(&path)?;
-                let hash = self.store_object(&content)?;
-                entries.push(TreeEntry {
-                    name,
-                    hash,
-                    is_file: true,
-                });
+                files.push(relative);
+            } else if is_dir {
+                Self::walk_dir(&path, &relative, rules, files)?;
             }
         }
-        
-        let tree_content = serde_json::to_vec(&entries)?;
-        self.store_object(&tree_content)
+
+        Ok(())
     }
 
-    /// Cria um novo commit
-    pub fn commit(&mut self, message: &str) -> Result<String, CogitError> {
-        let tree_hash = self.create_tree()?;
+    /// Cria um novo commit com o conteúdo do staging area
+    ///
+    /// Durante um merge, o commit integrado vira o segundo pai e o estado do
+    /// merge é encerrado; conflitos ainda marcados no index impedem o commit.
+    /// Um cherry-pick ou revert parado é concluído com `--continue`, não aqui.
+    pub fn commit(&mut self, message: &str, staging_area: &StagingArea) -> Result<String, CogitError> {
+        if let Some(kind) = self.pick_in_progress() {
+            return Err(CogitError::OperationInProgress(kind.name().to_string()));
+        }
+        let unmerged = staging_area.conflicted_paths();
+        if !unmerged.is_empty() {
+            return Err(CogitError::UnmergedPaths(unmerged));
+        }
+
+        // Identidade primeiro: sem ela nenhum objeto deve ser gravado
+        let (author, committer) = self.commit_signatures()?;
+
+        // Commit pai (se existir), lido antes da árvore: se o branch andar daqui
+        // até a gravação da referência, o commit é recusado
+        let head = self.head_commit()?;
+        let tree_hash = self.create_tree(staging_area)?;
+        
+        // Num merge, o commit integrado entra como pai extra
+        let mut parents: Vec<String> = head.iter().cloned().collect();
+        parents.extend(self.merge_heads()?);
+        let parent_count = parents.len();
+        
+        let stored_hash = self.write_commit(tree_hash, parents, message, author, committer)?;
         
-        // Busca o commit pai (se existir)
-        let parent = self.get_current_commit_hash().ok();
+        // Avança o branch atual (ou o próprio HEAD, se destacado)
+        let kind = match parent_count {
+            0 => "commit (inicial)",
+            1 => "commit",
+            _ => "commit (merge)",
+        };
+        self.update_head_commit(head.as_deref(), &stored_hash, &format!("{}: {}", kind, message))?;
+        self.clear_merge_state()?;
         
+        Ok(stored_hash)
+    }
+
+    /// Autor e committer de um novo commit, com as datas truncadas em segundos
+    /// (a precisão da codificação canônica)
+    pub fn commit_signatures(&self) -> Result<(Signature, Signature), CogitError> {
+        let now = Utc::now();
+        let mut author = self.signature(SignatureRole::Author, now)?;
+        let mut committer = self.signature(SignatureRole::Committer, now)?;
+        author.timestamp = author.timestamp.trunc_subsecs(0);
+        committer.timestamp = committer.timestamp.trunc_subsecs(0);
+        Ok((author, committer))
+    }
+
+    /// Grava um objeto de commit sem mover nenhuma referência
+    pub fn write_commit(
+        &self,
+        tree_hash: String,
+        parents: Vec<String>,
+        message: &str,
+        author: Signature,
+        committer: Signature,
+    ) -> Result<String, CogitError> {
         let commit = Commit {
-            hash: String::new(), // Temporário
+            hash: String::new(), // Conhecido só depois de gravar
             message: message.to_string(),
-            timestamp: Utc::now(),
-            parent,
+            timestamp: committer.timestamp,
+            parents,
             tree_hash,
+            author: Some(author),
+            committer: Some(committer),
         };
         
-        // Serializa o commit sem o hash para calcular o hash correto
-        let commit_content = serde_json::to_vec(&commit)?;
-        
-        // Armazena o commit com o hash calculado
-        let stored_hash = self.store_object(&commit_content)?;
+        // O hash é o da codificação canônica (que não inclui o próprio hash)
+        self.store_object(ObjectType::Commit, &commit.canonical_bytes()?)
+    }
+
+    /// Caminho de um objeto solto (objects/xx/yyyy)
+    pub fn loose_object_path(&self, hash: &str) -> PathBuf {
+        self.cogit_dir
+            .join("objects")
+            .join(&hash[..2])
+            .join(&hash[2..])
+    }
+
+    /// Verifica se um objeto existe, solto ou em um pack
+    pub fn has_object(&self, hash: &str) -> Result<bool, CogitError> {
+        Ok(self.loose_object_path(hash).exists() || self.packed_entry(hash)?.is_some())
+    }
+
+    /// Carrega o conteúdo de um objeto do armazenamento
+    pub fn load_object(&self, hash: &str) -> Result<Vec<u8>, CogitError> {
+        Ok(self.read_object(hash)?.1)
+    }
+
+    /// Carrega um objeto com o seu tipo, validando o cabeçalho
+    ///
+    /// Objetos soltos têm prioridade; se não existirem, o objeto é procurado nos packs.
+    pub fn read_object(&self, hash: &str) -> Result<(ObjectType, Vec<u8>), CogitError> {
+        if !is_full_hash(hash) {
+            return Err(CogitError::InvalidHash);
+        }
+        let object_path = self.loose_object_path(hash);
         
-        // Atualiza a referência HEAD
-        fs::write(self.cogit_dir.join("refs").join("heads").join("main"), &stored_hash)?;
+        if object_path.exists() {
+            return decode_object(hash, &fs::read(object_path)?);
+        }
         
-        Ok(stored_hash)
+        self.read_packed_object(hash)?.ok_or(CogitError::InvalidHash)
     }
 
-    /// Obtém o hash do commit atual
-    fn get_current_commit_hash(&self) -> Result<String, CogitError> {
-        let head_path = self.cogit_dir.join("refs").join("heads").join("main");
-        if head_path.exists() {
-            Ok(fs::read_to_string(head_path)?.trim().to_string())
-        } else {
-            Err(CogitError::NotARepository)
+    /// Verifica se um objeto foi gravado no formato legado (sem cabeçalho de tipo)
+    ///
+    /// O tipo desses objetos é apenas inferido pelo conteúdo: um blob com o JSON
+    /// de uma árvore vazia, por exemplo, é indistinguível da própria árvore.
+    pub fn is_legacy_object(&self, hash: &str) -> Result<bool, CogitError> {
+        let object_path = self.loose_object_path(hash);
+        if object_path.exists() {
+            return Ok(Self::calculate_hash(&fs::read(object_path)?) == hash);
         }
+        Ok(self.packed_entry(hash)?.is_some_and(|(_, entry)| entry.legacy))
     }
 
-    /// Carrega um objeto do armazenamento
-    fn load_object(&self, hash: &str) -> Result<Vec<u8>, CogitError> {
-        let object_path = self.cogit_dir
-            .join("objects")
-            .join(&hash[..2])
-            .join(&hash[2..]);
-        
-        if !object_path.exists() {
-            return Err(CogitError::InvalidHash);
+    /// Carrega um objeto exigindo que ele seja do tipo esperado
+    ///
+    /// Objetos legados são aceitos mesmo que o tipo inferido seja outro.
+    pub fn load_typed_object(&self, hash: &str, expected: ObjectType) -> Result<Vec<u8>, CogitError> {
+        let (object_type, content) = self.read_object(hash)?;
+        if object_type != expected && !self.is_legacy_object(hash)? {
+            return Err(CogitError::UnexpectedObjectType(hash.to_string(), expected));
         }
-        
-        Ok(fs::read(object_path)?)
+        Ok(content)
+    }
+
+    /// Carrega e desserializa as entradas de uma árvore
+    pub fn load_tree(&self, hash: &str) -> Result<Vec<TreeEntry>, CogitError> {
+        let tree_data = self.load_typed_object(hash, ObjectType::Tree)?;
+        Ok(serde_json::from_slice(&tree_data)?)
+    }
+
+    /// Carrega e desserializa um commit
+    pub fn load_commit(&self, hash: &str) -> Result<Commit, CogitError> {
+        let commit_data = self.load_typed_object(hash, ObjectType::Commit)?;
+        let mut commit: Commit = serde_json::from_slice(&commit_data)?;
+        commit.hash = hash.to_string();
+        Ok(commit)
     }
 
     /// Mostra o histórico de commits
+    ///
+    /// Todos os pais de um merge são seguidos; os commits saem do mais recente
+    /// para o mais antigo (pela data do committer), cada um uma única vez e
+    /// nunca antes de um filho: datas iguais (ou relógios adiantados) não
+    /// colocam um pai antes do commit que o referencia.
     pub fn log(&self) -> Result<Vec<Commit>, CogitError> {
-        let mut commits = Vec::new();
-        let mut current_hash = self.get_current_commit_hash().ok();
-        
-        while let Some(hash) = current_hash {
-            let commit_data = self.load_object(&hash)?;
-            let commit: Commit = serde_json::from_slice(&commit_data)?;
-            current_hash = commit.parent.clone();
+        let mut loaded = HashMap::new();
+        let mut children: HashMap<String, usize> = HashMap::new();
+        let mut walk: Vec<String> = self.head_commit()?.into_iter().collect();
+        while let Some(hash) = walk.pop() {
+            if loaded.contains_key(&hash) {
+                continue;
+            }
+            let commit = self.load_commit(&hash)?;
+            for parent in &commit.parents {
+                *children.entry(parent.clone()).or_default() += 1;
+                walk.push(parent.clone());
+            }
+            loaded.insert(hash, commit);
+        }
+
+        // Só entra na fila quem já teve todos os filhos listados
+        let mut pending: BinaryHeap<_> = loaded
+            .values()
+            .filter(|commit| !children.contains_key(&commit.hash))
+            .map(|commit| (commit.timestamp, commit.hash.clone()))
+            .collect();
+        let mut commits = Vec::with_capacity(loaded.len());
+
+        while let Some((_, hash)) = pending.pop() {
+            let Some(commit) = loaded.remove(&hash) else { continue };
+            for parent in &commit.parents {
+                let Some(count) = children.get_mut(parent) else { continue };
+                *count -= 1;
+                if *count == 0 {
+                    pending.push((loaded[parent].timestamp, parent.clone()));
+                }
+            }
             commits.push(commit);
         }
         
@@ -217,6 +697,10 @@ impl CogitRepository {
     /// Mostra o status atual do repositório
     pub fn status(&self) -> Result<String, CogitError> {
         let commit_count = self.log()?.len();
-        Ok(format!("Repositório COGIT com {} commit(s)", commit_count))
+        let location = match self.read_head()? {
+            Head::Branch(name) => format!("No branch {}", name),
+            Head::Detached(hash) => format!("HEAD destacado em {}", &hash[..7]),
+        };
+        Ok(format!("{} - Repositório COGIT com {} commit(s)", location, commit_count))
     }
 } 
\ No newline at end of file
@@ -0,0 +1,168 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::lockfile::LockFile;
+
+/// Variável que substitui o caminho da configuração do usuário
+const GLOBAL_CONFIG_ENV: &str = "COGIT_CONFIG_GLOBAL";
+
+/// Nome do arquivo de configuração do usuário, dentro do diretório home
+const GLOBAL_CONFIG_FILE: &str = ".cogitconfig.json";
+
+/// Identidade de quem criou (autor) ou registrou (committer) um commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl std::fmt::Display for Signature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+/// Papel de uma assinatura, que define as variáveis de ambiente consultadas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureRole {
+    Author,     // COGIT_AUTHOR_NAME / _EMAIL / _DATE
+    Committer,  // COGIT_COMMITTER_NAME / _EMAIL / _DATE
+}
+
+impl SignatureRole {
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            SignatureRole::Author => "COGIT_AUTHOR",
+            SignatureRole::Committer => "COGIT_COMMITTER",
+        }
+    }
+}
+
+/// Caminho da configuração do usuário (`~/.cogitconfig.json`, ou `COGIT_CONFIG_GLOBAL`)
+pub fn global_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(GLOBAL_CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(GLOBAL_CONFIG_FILE))
+}
+
+/// Lê uma chave no formato `secao.nome` de um arquivo de configuração JSON
+pub fn read_config_value(path: &Path, key: &str) -> Result<Option<String>, CogitError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let config: Value = serde_json::from_slice(&fs::read(path)?)?;
+    let value = key
+        .split('.')
+        .try_fold(&config, |node, part| node.get(part))
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+    Ok(value)
+}
+
+/// Grava uma chave no formato `secao.nome`, preservando o restante do arquivo
+pub fn write_config_value(path: &Path, key: &str, value: &str) -> Result<(), CogitError> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.len() < 2 || parts.iter().any(|part| part.is_empty()) {
+        return Err(CogitError::InvalidConfig(format!("chave '{}' deve ter o formato secao.nome", key)));
+    }
+
+    let lock = LockFile::acquire(path)?;
+    let mut config = match path.is_file() {
+        true => serde_json::from_slice(&fs::read(path)?)?,
+        false => Value::Object(Map::new()),
+    };
+
+    let mut node = &mut config;
+    for part in &parts[..parts.len() - 1] {
+        let object = node
+            .as_object_mut()
+            .ok_or_else(|| CogitError::InvalidConfig(format!("'{}' não é uma seção", part)))?;
+        node = object
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    node.as_object_mut()
+        .ok_or_else(|| CogitError::InvalidConfig(format!("'{}' não é uma seção", key)))?
+        .insert(parts[parts.len() - 1].to_string(), Value::String(value.to_string()));
+
+    lock.commit(serde_json::to_string_pretty(&config)?.as_bytes())
+}
+
+/// Interpreta uma data de `COGIT_*_DATE`: RFC 3339 ou segundos desde a época Unix
+fn parse_signature_date(variable: &str, value: &str) -> Result<DateTime<Utc>, CogitError> {
+    let value = value.trim();
+    let seconds = value.strip_prefix('@').unwrap_or(value);
+
+    if let Ok(seconds) = seconds.parse::<i64>() {
+        if let Some(date) = Utc.timestamp_opt(seconds, 0).single() {
+            return Ok(date);
+        }
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| CogitError::InvalidConfig(format!("{} inválida: '{}'", variable, value)))
+}
+
+impl CogitRepository {
+    /// Caminho da configuração do repositório (.cogit/config.json)
+    pub fn config_path(&self) -> PathBuf {
+        self.cogit_dir().join("config.json")
+    }
+
+    /// Valor de uma chave: a configuração do repositório tem prioridade sobre a do usuário
+    pub fn config_value(&self, key: &str) -> Result<Option<String>, CogitError> {
+        if let Some(value) = read_config_value(&self.config_path(), key)? {
+            return Ok(Some(value));
+        }
+        match global_config_path() {
+            Some(path) => read_config_value(&path, key),
+            None => Ok(None),
+        }
+    }
+
+    /// Monta a assinatura de autor ou committer
+    ///
+    /// Nome e e-mail vêm de `COGIT_<PAPEL>_NAME`/`_EMAIL`, ou de `user.name` e
+    /// `user.email` na configuração; a data vem de `COGIT_<PAPEL>_DATE` (útil
+    /// para testes reproduzíveis) ou de `now`.
+    pub fn signature(&self, role: SignatureRole, now: DateTime<Utc>) -> Result<Signature, CogitError> {
+        let prefix = role.env_prefix();
+        let env = |suffix: &str| {
+            std::env::var(format!("{}_{}", prefix, suffix))
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+
+        let name = match env("NAME") {
+            Some(name) => name,
+            None => self.config_value("user.name")?.ok_or_else(|| missing_identity("user.name"))?,
+        };
+        let email = match env("EMAIL") {
+            Some(email) => email,
+            None => self.config_value("user.email")?.ok_or_else(|| missing_identity("user.email"))?,
+        };
+        let timestamp = match env("DATE") {
+            Some(date) => parse_signature_date(&format!("{}_DATE", prefix), &date)?,
+            None => now,
+        };
+
+        Ok(Signature { name, email, timestamp })
+    }
+}
+
+fn missing_identity(key: &str) -> CogitError {
+    CogitError::InvalidConfig(format!(
+        "identidade não configurada ({}); use 'cogit config --global {} <valor>'",
+        key, key
+    ))
+}
@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::cogit::CogitError;
+use crate::cogit::{CogitError, CogitRepository};
+use crate::lockfile::LockFile;
+use crate::object::ObjectType;
+use crate::refs::is_full_hash;
 
 /// Representa uma linha em um diff
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +15,8 @@ pub struct DiffLine {
     pub line_number: usize,
     pub content: String,
     pub change_type: LineChangeType,
+    #[serde(default)]
+    pub no_newline: bool,  // Última linha do arquivo, sem quebra de linha no final
 }
 
 /// Tipo de mudança em uma linha
@@ -37,8 +42,10 @@ pub struct DiffHunk {
 pub struct FileDiff {
     pub file_path: String,
     pub old_hash: Option<String>,  // None se arquivo é novo
-    pub new_hash: String,
+    pub new_hash: Option<String>,  // None se arquivo foi removido
     pub change_type: FileChangeType,
+    #[serde(default)]
+    pub is_binary: bool,  // Conteúdo binário: sem hunks, apenas "Binary files differ"
     pub hunks: Vec<DiffHunk>,
     pub patch_content: String,  // Conteúdo textual do patch
     pub created_at: DateTime<Utc>,
@@ -71,6 +78,7 @@ pub enum WorkingTreeStatus {
     Staged,       // Adicionado ao staging area
     Deleted,      // Deletado
     Unchanged,    // Sem mudanças
+    Conflicted,   // Conflito de merge não resolvido
 }
 
 /// Staging area (index) - similar ao git index
@@ -80,6 +88,28 @@ pub struct StagingArea {
     pub last_updated: DateTime<Utc>,
 }
 
+impl StagingArea {
+    /// Staging area sem entradas
+    pub fn empty() -> Self {
+        Self {
+            entries: HashMap::new(),
+            last_updated: Utc::now(),
+        }
+    }
+
+    /// Caminhos ainda marcados com conflito, em ordem
+    pub fn conflicted_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self
+            .entries
+            .values()
+            .filter(|entry| entry.conflicted)
+            .map(|entry| entry.file_path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+}
+
 /// Entrada no staging area
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StagingEntry {
@@ -87,6 +117,40 @@ pub struct StagingEntry {
     pub content_hash: String,
     pub file_size: u64,
     pub staged_at: DateTime<Utc>,
+    #[serde(default)]
+    pub deleted: bool,  // Remoção staged: o arquivo sai do próximo commit
+    #[serde(default)]
+    pub conflicted: bool,  // Conflito de merge ainda não resolvido (resolvido no próximo add)
+}
+
+impl StagingEntry {
+    /// Entrada para um blob já gravado no object store
+    pub fn blob(file_path: &str, content_hash: &str, file_size: u64) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            content_hash: content_hash.to_string(),
+            file_size,
+            staged_at: Utc::now(),
+            deleted: false,
+            conflicted: false,
+        }
+    }
+
+    /// Entrada que registra a remoção de um arquivo rastreado
+    pub fn deletion(file_path: &str) -> Self {
+        Self { deleted: true, ..Self::blob(file_path, "", 0) }
+    }
+}
+
+/// Linhas de contexto mostradas antes e depois de cada mudança
+const DIFF_CONTEXT: usize = 3;
+
+/// Operação do script de edição: índices (base 0) nas linhas antiga/nova
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditOp {
+    Equal(usize, usize),  // Linha presente nas duas versões
+    Delete(usize),        // Linha removida da versão antiga
+    Insert(usize),        // Linha inserida da versão nova
 }
 
 /// Motor de diff - implementa algoritmos de comparação
@@ -100,178 +164,129 @@ impl DiffEngine {
         Self { cogit_dir }
     }
     
-    /// Calcula diff entre duas versões de um arquivo
+    /// Calcula diff entre duas versões de um arquivo (None = arquivo inexistente naquele lado)
+    ///
+    /// Os hashes são os nomes de blob dos bytes brutos; se algum dos lados
+    /// for binário, o diff não tem hunks e o patch apenas indica que os arquivos diferem.
     pub fn calculate_file_diff(
         &self,
         file_path: &Path,
-        old_content: Option<&str>,
-        new_content: &str,
+        old_content: Option<&[u8]>,
+        new_content: Option<&[u8]>,
     ) -> Result<FileDiff, CogitError> {
-        let old_hash = old_content.map(|content| 
-            crate::cogit::CogitRepository::calculate_hash(content.as_bytes())
-        );
-        let new_hash = crate::cogit::CogitRepository::calculate_hash(new_content.as_bytes());
-        
-        let change_type = match old_content {
-            None => FileChangeType::Added,
-            Some(old) if old == new_content => return Err(CogitError::IoError(
+        let old_hash = old_content.map(CogitRepository::blob_hash);
+        let new_hash = new_content.map(CogitRepository::blob_hash);
+        
+        let change_type = match (old_content, new_content) {
+            (None, None) => return Err(CogitError::IoError(
+                std::io::Error::new(std::io::ErrorKind::NotFound, "Arquivo não encontrado")
+            )),
+            (Some(old), Some(new)) if old == new => return Err(CogitError::IoError(
                 std::io::Error::new(std::io::ErrorKind::InvalidInput, "Arquivo sem mudanças")
             )),
-            Some(_) => FileChangeType::Modified,
+            (None, Some(_)) => FileChangeType::Added,
+            (Some(_), None) => FileChangeType::Deleted,
+            (Some(_), Some(_)) => FileChangeType::Modified,
         };
         
-        let hunks = if let Some(old) = old_content {
-            self.calculate_hunks(old, new_content)?
+        let binary = old_content.is_some_and(is_binary) || new_content.is_some_and(is_binary);
+        
+        let (hunks, patch_content) = if binary {
+            let path = normalize_path(file_path);
+            let old_name = if old_content.is_some() { format!("a/{}", path) } else { "/dev/null".to_string() };
+            let new_name = if new_content.is_some() { format!("b/{}", path) } else { "/dev/null".to_string() };
+            (Vec::new(), format!("Binary files {} and {} differ\n", old_name, new_name))
         } else {
-            // Arquivo novo - todo conteúdo é uma adição
-            vec![self.create_addition_hunk(new_content)]
+            // Arquivo novo ou removido: o lado ausente é tratado como vazio
+            let old_text = String::from_utf8_lossy(old_content.unwrap_or_default());
+            let new_text = String::from_utf8_lossy(new_content.unwrap_or_default());
+            let hunks = self.calculate_hunks(&old_text, &new_text)?;
+            let patch_content = self.generate_patch_content(&hunks, file_path, &change_type)?;
+            (hunks, patch_content)
         };
         
-        let patch_content = self.generate_patch_content(&hunks, file_path)?;
-        
         Ok(FileDiff {
-            file_path: file_path.to_string_lossy().to_string(),
+            file_path: normalize_path(file_path),
             old_hash,
             new_hash,
             change_type,
+            is_binary: binary,
             hunks,
             patch_content,
             created_at: Utc::now(),
         })
     }
     
-    /// Calcula hunks (blocos de mudanças) usando algoritmo de diff simples
+    /// Calcula hunks (blocos de mudanças) a partir do script de edição mínimo (Myers)
+    ///
+    /// Mudanças separadas por mais de `2 * DIFF_CONTEXT` linhas iguais viram hunks
+    /// distintos; cada hunk carrega até `DIFF_CONTEXT` linhas de contexto em volta.
+    /// As linhas são comparadas com a quebra de linha, então acrescentar ou
+    /// remover o "\n" final também muda a última linha.
     fn calculate_hunks(&self, old_content: &str, new_content: &str) -> Result<Vec<DiffHunk>, CogitError> {
-        let old_lines: Vec<&str> = old_content.lines().collect();
-        let new_lines: Vec<&str> = new_content.lines().collect();
+        let old_lines: Vec<&str> = old_content.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new_content.split_inclusive('\n').collect();
+        let ops = myers_diff(&old_lines, &new_lines);
         
-        // Implementação simples de diff - algoritmo Myers básico
         let mut hunks = Vec::new();
-        let mut old_idx = 0;
-        let mut new_idx = 0;
-        
-        while old_idx < old_lines.len() || new_idx < new_lines.len() {
-            let mut hunk_lines = Vec::new();
-            let hunk_old_start = old_idx + 1;
-            let hunk_new_start = new_idx + 1;
-            let mut hunk_old_count = 0;
-            let mut hunk_new_count = 0;
-            
-            // Adicionar contexto antes das mudanças
-            let context_before = 3;
-            let context_start = old_idx.saturating_sub(context_before);
-            for i in context_start..old_idx {
-                if i < old_lines.len() {
-                    hunk_lines.push(DiffLine {
-                        line_number: i + 1,
-                        content: old_lines[i].to_string(),
-                        change_type: LineChangeType::Context,
-                    });
-                    hunk_old_count += 1;
-                    hunk_new_count += 1;
-                }
-            }
-            
-            // Detectar mudanças (implementação simplificada)
-            while old_idx < old_lines.len() && new_idx < new_lines.len() {
-                if old_lines[old_idx] == new_lines[new_idx] {
-                    // Linha igual - contexto
-                    hunk_lines.push(DiffLine {
-                        line_number: old_idx + 1,
-                        content: old_lines[old_idx].to_string(),
-                        change_type: LineChangeType::Context,
-                    });
-                    hunk_old_count += 1;
-                    hunk_new_count += 1;
-                    old_idx += 1;
-                    new_idx += 1;
-                } else {
-                    // Linhas diferentes - remoção + adição
-                    hunk_lines.push(DiffLine {
-                        line_number: old_idx + 1,
-                        content: old_lines[old_idx].to_string(),
-                        change_type: LineChangeType::Removed,
-                    });
-                    hunk_old_count += 1;
-                    old_idx += 1;
-                    
-                    hunk_lines.push(DiffLine {
-                        line_number: new_idx + 1,
-                        content: new_lines[new_idx].to_string(),
-                        change_type: LineChangeType::Added,
-                    });
-                    hunk_new_count += 1;
-                    new_idx += 1;
-                    break;
-                }
-            }
-            
-            // Processar linhas restantes
-            while old_idx < old_lines.len() {
-                hunk_lines.push(DiffLine {
-                    line_number: old_idx + 1,
-                    content: old_lines[old_idx].to_string(),
-                    change_type: LineChangeType::Removed,
-                });
-                hunk_old_count += 1;
-                old_idx += 1;
+        let mut previous_end = 0;
+        let mut idx = 0;
+        
+        while idx < ops.len() {
+            if let EditOp::Equal(_, _) = ops[idx] {
+                idx += 1;
+                continue;
             }
             
-            while new_idx < new_lines.len() {
-                hunk_lines.push(DiffLine {
-                    line_number: new_idx + 1,
-                    content: new_lines[new_idx].to_string(),
-                    change_type: LineChangeType::Added,
-                });
-                hunk_new_count += 1;
-                new_idx += 1;
-            }
+            let start = idx.saturating_sub(DIFF_CONTEXT).max(previous_end);
             
-            if !hunk_lines.is_empty() {
-                hunks.push(DiffHunk {
-                    old_start: hunk_old_start,
-                    old_count: hunk_old_count,
-                    new_start: hunk_new_start,
-                    new_count: hunk_new_count,
-                    lines: hunk_lines,
-                });
-            }
+            // Estende o hunk enquanto as mudanças seguintes estiverem próximas
+            let mut cursor = idx;
+            let end = loop {
+                while cursor < ops.len() && !matches!(ops[cursor], EditOp::Equal(_, _)) {
+                    cursor += 1;
+                }
+                let equal_start = cursor;
+                while cursor < ops.len() && matches!(ops[cursor], EditOp::Equal(_, _)) {
+                    cursor += 1;
+                }
+                let equal_run = cursor - equal_start;
+                
+                if cursor >= ops.len() {
+                    break equal_start + equal_run.min(DIFF_CONTEXT);
+                }
+                if equal_run > 2 * DIFF_CONTEXT {
+                    break equal_start + DIFF_CONTEXT;
+                }
+            };
             
-            break; // Por agora, um hunk por vez (simplificado)
+            hunks.push(build_hunk(&ops[start..end], &old_lines, &new_lines));
+            previous_end = end;
+            idx = end;
         }
         
         Ok(hunks)
     }
     
-    /// Cria hunk para arquivo completamente novo
-    fn create_addition_hunk(&self, content: &str) -> DiffHunk {
-        let lines: Vec<&str> = content.lines().collect();
-        let diff_lines: Vec<DiffLine> = lines
-            .iter()
-            .enumerate()
-            .map(|(idx, line)| DiffLine {
-                line_number: idx + 1,
-                content: line.to_string(),
-                change_type: LineChangeType::Added,
-            })
-            .collect();
-        
-        DiffHunk {
-            old_start: 0,
-            old_count: 0,
-            new_start: 1,
-            new_count: lines.len(),
-            lines: diff_lines,
-        }
-    }
-    
     /// Gera conteúdo do patch no formato unified diff
-    fn generate_patch_content(&self, hunks: &[DiffHunk], file_path: &Path) -> Result<String, CogitError> {
+    fn generate_patch_content(
+        &self,
+        hunks: &[DiffHunk],
+        file_path: &Path,
+        change_type: &FileChangeType,
+    ) -> Result<String, CogitError> {
         let mut patch = String::new();
+        let path = normalize_path(file_path);
         
-        // Header do patch
-        patch.push_str(&format!("--- a/{}\n", file_path.display()));
-        patch.push_str(&format!("+++ b/{}\n", file_path.display()));
+        // Header do patch (/dev/null no lado inexistente)
+        match change_type {
+            FileChangeType::Added => patch.push_str("--- /dev/null\n"),
+            _ => patch.push_str(&format!("--- a/{}\n", path)),
+        }
+        match change_type {
+            FileChangeType::Deleted => patch.push_str("+++ /dev/null\n"),
+            _ => patch.push_str(&format!("+++ b/{}\n", path)),
+        }
         
         for hunk in hunks {
             // Header do hunk
@@ -289,6 +304,9 @@ impl DiffEngine {
                     LineChangeType::Context => " ",
                 };
                 patch.push_str(&format!("{}{}\n", prefix, line.content));
+                if line.no_newline {
+                    patch.push_str("\\ No newline at end of file\n");
+                }
             }
         }
         
@@ -300,23 +318,45 @@ impl DiffEngine {
         let index_path = self.cogit_dir.join("index.json");
         
         if !index_path.exists() {
-            return Ok(StagingArea {
-                entries: HashMap::new(),
-                last_updated: Utc::now(),
-            });
+            return Ok(StagingArea::empty());
         }
         
         let content = fs::read_to_string(index_path)?;
         let staging_area: StagingArea = serde_json::from_str(&content)?;
+
+        // Os hashes viram caminhos no object store: um index editado à mão ou
+        // truncado é recusado aqui em vez de quebrar quem o usa
+        if let Some((path, _)) = staging_area
+            .entries
+            .iter()
+            .find(|(_, entry)| !entry.deleted && !is_full_hash(&entry.content_hash))
+        {
+            return Err(CogitError::CorruptObject(
+                "index.json".to_string(),
+                format!("hash inválido na entrada de {}", path),
+            ));
+        }
         Ok(staging_area)
     }
     
-    /// Salva staging area no disco
-    pub fn save_staging_area(&self, staging_area: &StagingArea) -> Result<(), CogitError> {
-        let index_path = self.cogit_dir.join("index.json");
+    /// Esvazia o staging area sob um lock já adquirido: o index volta a ser
+    /// igual ao HEAD
+    pub fn clear_staging_area(&self, lock: LockFile) -> Result<(), CogitError> {
+        self.write_staging_area(lock, &StagingArea::empty())
+    }
+
+    /// Adquire o lock do index (index.json.lock)
+    ///
+    /// Quem vai ler, alterar e salvar o staging area deve segurar o lock desde
+    /// a leitura, para não sobrescrever mudanças de outro processo.
+    pub fn lock_staging_area(&self) -> Result<LockFile, CogitError> {
+        LockFile::acquire(&self.cogit_dir.join("index.json"))
+    }
+
+    /// Salva o staging area sob um lock já adquirido, liberando-o
+    pub fn write_staging_area(&self, lock: LockFile, staging_area: &StagingArea) -> Result<(), CogitError> {
         let content = serde_json::to_string_pretty(staging_area)?;
-        fs::write(index_path, content)?;
-        Ok(())
+        lock.commit(content.as_bytes())
     }
     
     /// Adiciona arquivo ao staging area
@@ -327,185 +367,606 @@ impl DiffEngine {
             ));
         }
         
-        let content = fs::read_to_string(file_path)?;
-        let content_hash = crate::cogit::CogitRepository::calculate_hash(content.as_bytes());
-        let metadata = fs::metadata(file_path)?;
+        // Grava o blob no object store: o snapshot staged fica imutável mesmo
+        // que o arquivo mude depois do add
+        let content = fs::read(file_path)?;
+        let content_hash = self.repository()?.store_object(ObjectType::Blob, &content)?;
+        let normalized_path = normalize_path(file_path);
         
+        let lock = self.lock_staging_area()?;
         let mut staging_area = self.load_staging_area()?;
         
-        let entry = StagingEntry {
-            file_path: file_path.to_string_lossy().to_string(),
-            content_hash,
-            file_size: metadata.len(),
-            staged_at: Utc::now(),
-        };
-        
-        staging_area.entries.insert(
-            file_path.to_string_lossy().to_string(),
-            entry
-        );
+        let entry = StagingEntry::blob(&normalized_path, &content_hash, content.len() as u64);
+        staging_area.entries.insert(normalized_path, entry);
         staging_area.last_updated = Utc::now();
         
-        self.save_staging_area(&staging_area)?;
-        Ok(())
+        self.write_staging_area(lock, &staging_area)
+    }
+    
+    /// Registra no staging area a remoção de um arquivo rastreado
+    ///
+    /// Se o arquivo não existe no HEAD (apenas foi adicionado), a entrada staged
+    /// é simplesmente descartada.
+    pub fn stage_deletion(&mut self, file_path: &Path) -> Result<(), CogitError> {
+        let normalized_path = normalize_path(file_path);
+        let lock = self.lock_staging_area()?;
+        let head_files = self.get_head_files()?;
+        let mut staging_area = self.load_staging_area()?;
+        
+        if head_files.contains_key(&normalized_path) {
+            staging_area.entries.insert(normalized_path.clone(), StagingEntry::deletion(&normalized_path));
+        } else if staging_area.entries.remove(&normalized_path).is_none() {
+            return Err(CogitError::IoError(
+                std::io::Error::new(std::io::ErrorKind::NotFound, format!("'{}' não é rastreado", normalized_path))
+            ));
+        }
+        
+        staging_area.last_updated = Utc::now();
+        self.write_staging_area(lock, &staging_area)
     }
     
     /// Lista status de todos os arquivos
     pub fn get_status(&self, root_path: &Path) -> Result<Vec<FileStatus>, CogitError> {
         let mut status_list = Vec::new();
         let staging_area = self.load_staging_area()?;
+        let repo = self.repository()?;
         
         // Obter arquivos do último commit (HEAD) se existir
         let head_files = self.get_head_files()?;
         
-        // Percorrer arquivos no working directory
-        for entry in fs::read_dir(root_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_file() && !path.starts_with(root_path.join(".cogit")) {
-                // Normalizar nome do arquivo para comparação (remover ./ do início)
-                let file_path = if let Ok(relative) = path.strip_prefix(root_path) {
-                    relative.to_string_lossy().to_string()
-                } else {
-                    path.to_string_lossy().to_string()
-                };
-                
-                // Calcular hash atual
-                let current_content = fs::read_to_string(&path)?;
-                let working_tree_hash = Some(
-                    crate::cogit::CogitRepository::calculate_hash(current_content.as_bytes())
-                );
-                
-                // Verificar se está no staging
-                let index_hash = staging_area.entries.get(&file_path)
-                    .map(|entry| entry.content_hash.clone());
-                
-                // Verificar hash no HEAD
-                let head_hash = head_files.get(&file_path).cloned();
-                
-                // Determinar status baseado em staging, working tree e HEAD
-                let status = match (&index_hash, &head_hash, &working_tree_hash) {
-                    // Arquivo staged (seja novo ou modificado)
-                    (Some(staged_hash), head_hash_opt, Some(work_hash)) 
-                        if staged_hash == work_hash => WorkingTreeStatus::Staged,
-                    
-                    // Arquivo modificado após staging
-                    (Some(_), _, _) => WorkingTreeStatus::Modified,
-                    
-                    // Arquivo não está staged
-                    (None, Some(head_hash_val), Some(work_hash)) => {
-                        if head_hash_val == work_hash {
-                            WorkingTreeStatus::Unchanged
-                        } else {
-                            WorkingTreeStatus::Modified
-                        }
+        // Percorrer arquivos do working tree (incluindo subdiretórios), do HEAD e do
+        // staging area: arquivos rastreados que sumiram do disco aparecem como removidos
+        let working_files = CogitRepository::walk_working_tree(root_path)?;
+        let all_paths: std::collections::BTreeSet<String> = working_files
+            .into_iter()
+            .chain(head_files.keys().cloned())
+            .chain(staging_area.entries.keys().cloned())
+            .collect();
+        
+        for file_path in all_paths {
+            let path = root_path.join(&file_path);
+
+            // Calcular hash atual (None se o arquivo não existe mais)
+            let working_tree_hash = if path.is_file() {
+                Some(repo.working_blob_hash(&fs::read(&path)?)?)
+            } else {
+                None
+            };
+
+            // Verificar se está no staging (remoções staged não têm hash)
+            let staged_entry = staging_area.entries.get(&file_path);
+            let staged_deletion = staged_entry.is_some_and(|entry| entry.deleted);
+            let index_hash = staged_entry
+                .filter(|entry| !entry.deleted)
+                .map(|entry| entry.content_hash.clone());
+
+            // Verificar hash no HEAD
+            let head_hash = head_files.get(&file_path).cloned();
+
+            // Determinar status baseado em staging, working tree e HEAD
+            let status = match (&index_hash, &head_hash, &working_tree_hash) {
+                // Conflito de merge: só sai deste estado com um novo add (ou rm)
+                _ if staged_entry.is_some_and(|entry| entry.conflicted) => WorkingTreeStatus::Conflicted,
+
+                // Remoção já registrada no staging area (com --cached o arquivo
+                // continua no disco e volta a ser não rastreado após o commit)
+                _ if staged_deletion => WorkingTreeStatus::Staged,
+
+                // Arquivo rastreado que não existe mais no disco
+                (Some(_), _, None) | (None, Some(_), None) => WorkingTreeStatus::Deleted,
+
+                // Arquivo staged (seja novo ou modificado)
+                (Some(staged_hash), _, Some(work_hash))
+                    if staged_hash == work_hash => WorkingTreeStatus::Staged,
+
+                // Arquivo modificado após staging
+                (Some(_), _, _) => WorkingTreeStatus::Modified,
+
+                // Arquivo não está staged
+                (None, Some(head_hash_val), Some(work_hash)) => {
+                    if head_hash_val == work_hash {
+                        WorkingTreeStatus::Unchanged
+                    } else {
+                        WorkingTreeStatus::Modified
                     }
-                    
-                    // Arquivo novo (não tracked em nenhum commit)
-                    (None, None, _) => WorkingTreeStatus::Untracked,
-                    
-                    _ => WorkingTreeStatus::Untracked,
-                };
-                
-                status_list.push(FileStatus {
-                    file_path,
-                    working_tree_hash,
-                    index_hash,
-                    head_hash,
-                    status,
-                });
-            }
+                }
+
+                // Arquivo novo (não tracked em nenhum commit)
+                (None, None, _) => WorkingTreeStatus::Untracked,
+            };
+
+            status_list.push(FileStatus {
+                file_path,
+                working_tree_hash,
+                index_hash,
+                head_hash,
+                status,
+            });
         }
         
         Ok(status_list)
     }
     
-    /// Obtém arquivos do último commit (HEAD)
-    fn get_head_files(&self) -> Result<HashMap<String, String>, CogitError> {
-        use crate::cogit::{CogitRepository, TreeEntry};
-        
-        let mut head_files = HashMap::new();
-        
-        // Tentar abrir repositório e obter último commit
-        let repo = CogitRepository::open(self.cogit_dir.parent().unwrap_or(std::path::Path::new(".")))?;
-        
-        if let Ok(commits) = repo.log() {
-            if let Some(last_commit) = commits.first() {
-                // Carregar tree do último commit
-                if let Ok(tree_data) = self.load_object(&last_commit.tree_hash) {
-                    if let Ok(tree_entries) = serde_json::from_slice::<Vec<TreeEntry>>(&tree_data) {
-                        for entry in tree_entries {
-                            if entry.is_file {
-                                head_files.insert(entry.name, entry.hash);
-                            }
-                        }
-                    }
-                }
+    /// Abre o repositório ao qual este motor pertence (acesso ao object store)
+    fn repository(&self) -> Result<CogitRepository, CogitError> {
+        CogitRepository::open(self.cogit_dir.parent().unwrap_or(Path::new(".")))
+    }
+    
+    /// Obtém arquivos do último commit (HEAD), já achatados em caminhos relativos
+    fn get_head_files(&self) -> Result<BTreeMap<String, String>, CogitError> {
+        self.repository()?.head_files()
+    }
+    
+    /// Diffs do working tree contra o index (HEAD + entradas staged)
+    ///
+    /// Assim como no git, arquivos não rastreados não aparecem aqui.
+    pub fn diff_working_tree(&self, root_path: &Path, paths: &[String]) -> Result<Vec<FileDiff>, CogitError> {
+        let repo = self.repository()?;
+        let index_files = repo.index_files()?;
+        
+        let mut working_files = BTreeMap::new();
+        for path in index_files.keys() {
+            if let Ok(content) = fs::read(root_path.join(path)) {
+                working_files.insert(path.clone(), repo.working_blob_hash(&content)?);
             }
         }
         
-        Ok(head_files)
+        self.diff_file_maps(&index_files, &working_files, paths, |path, _| {
+            Ok(fs::read(root_path.join(path))?)
+        })
     }
     
-    /// Carrega um objeto do armazenamento (helper method)
-    fn load_object(&self, hash: &str) -> Result<Vec<u8>, CogitError> {
-        let object_path = self.cogit_dir
-            .join("objects")
-            .join(&hash[..2])
-            .join(&hash[2..]);
-        
-        if !object_path.exists() {
-            return Err(CogitError::InvalidHash);
-        }
+    /// Diffs do index (HEAD + entradas staged) contra o HEAD
+    pub fn diff_staged(&self, paths: &[String]) -> Result<Vec<FileDiff>, CogitError> {
+        let repo = self.repository()?;
+        let head_files = repo.head_files()?;
+        let index_files = repo.index_files()?;
         
-        Ok(fs::read(object_path)?)
+        self.diff_file_maps(&head_files, &index_files, paths, |_, hash| repo.load_object(hash))
     }
     
-    /// Mostra diff de um arquivo específico
-    pub fn show_file_diff(&self, file_path: &Path) -> Result<(), CogitError> {
-        if !file_path.exists() {
-            return Err(CogitError::IoError(
-                std::io::Error::new(std::io::ErrorKind::NotFound, "Arquivo não encontrado")
-            ));
-        }
+    /// Diffs entre as árvores de dois commits (arquivos adicionados, modificados e removidos)
+    pub fn diff_commits(&self, old_commit: &str, new_commit: &str, paths: &[String]) -> Result<Vec<FileDiff>, CogitError> {
+        let repo = self.repository()?;
+        let old_files = repo.commit_files(old_commit)?;
+        let new_files = repo.commit_files(new_commit)?;
         
-        let current_content = fs::read_to_string(file_path)?;
+        self.diff_file_maps(&old_files, &new_files, paths, |_, hash| repo.load_object(hash))
+    }
+    
+    /// Diffs entre a árvore de um commit e o working tree (arquivos rastreados)
+    pub fn diff_commit_working_tree(&self, root_path: &Path, commit: &str, paths: &[String]) -> Result<Vec<FileDiff>, CogitError> {
+        let repo = self.repository()?;
+        let commit_files = repo.commit_files(commit)?;
+        
+        let mut working_files = BTreeMap::new();
+        for path in commit_files.keys().chain(repo.index_files()?.keys()) {
+            if let Ok(content) = fs::read(root_path.join(path)) {
+                working_files.insert(path.clone(), repo.working_blob_hash(&content)?);
+            }
+        }
         
-        // Por agora, vamos comparar com "arquivo vazio" para mostrar todo conteúdo como adição
-        // TODO: Implementar comparação com última versão commitada
-        let diff = self.calculate_file_diff(file_path, None, &current_content)?;
+        self.diff_file_maps(&commit_files, &working_files, paths, |path, _| {
+            Ok(fs::read(root_path.join(path))?)
+        })
+    }
+    
+    /// Compara dois mapas caminho -> hash, lendo o lado antigo do object store
+    /// e o lado novo com `read_new(caminho, hash)`
+    fn diff_file_maps<F>(
+        &self,
+        old_files: &BTreeMap<String, String>,
+        new_files: &BTreeMap<String, String>,
+        paths: &[String],
+        read_new: F,
+    ) -> Result<Vec<FileDiff>, CogitError>
+    where
+        F: Fn(&str, &str) -> Result<Vec<u8>, CogitError>,
+    {
+        let repo = self.repository()?;
+        let all_paths: std::collections::BTreeSet<&String> = old_files.keys().chain(new_files.keys()).collect();
+        let mut diffs = Vec::new();
+        
+        for path in all_paths {
+            if !paths.is_empty() && !paths.iter().any(|pattern| path_matches(path, pattern)) {
+                continue;
+            }
+            
+            let old_hash = old_files.get(path);
+            let new_hash = new_files.get(path);
+            if old_hash == new_hash {
+                continue;
+            }
+            
+            let old_content = match old_hash {
+                Some(hash) => Some(repo.load_object(hash)?),
+                None => None,
+            };
+            let new_content = match new_hash {
+                Some(hash) => Some(read_new(path, hash)?),
+                None => None,
+            };
+            
+            diffs.push(self.calculate_file_diff(Path::new(path), old_content.as_deref(), new_content.as_deref())?);
+        }
         
-        println!("diff --git a/{} b/{}", file_path.display(), file_path.display());
-        println!("new file mode 100644");
-        println!("index 0000000..{}", &diff.new_hash[..7]);
-        println!("{}", diff.patch_content);
+        Ok(diffs)
+    }
+    
+    /// Imprime um diff no formato do git (header + patch)
+    pub fn print_file_diff(&self, diff: &FileDiff) {
+        const NULL_HASH: &str = "0000000";
+        let old_short = diff.old_hash.as_deref().map_or(NULL_HASH, |hash| &hash[..7]);
+        let new_short = diff.new_hash.as_deref().map_or(NULL_HASH, |hash| &hash[..7]);
+        
+        println!("diff --git a/{} b/{}", diff.file_path, diff.file_path);
+        match diff.change_type {
+            FileChangeType::Added => {
+                println!("new file mode 100644");
+                println!("index {}..{}", old_short, new_short);
+            }
+            FileChangeType::Deleted => {
+                println!("deleted file mode 100644");
+                println!("index {}..{}", old_short, new_short);
+            }
+            _ => println!("index {}..{} 100644", old_short, new_short),
+        }
+        print!("{}", diff.patch_content);
+    }
+    
+    /// Mostra diffs do working tree (ou do index, com `staged`) para os caminhos dados
+    pub fn show_diffs(&self, root_path: &Path, staged: bool, paths: &[String]) -> Result<(), CogitError> {
+        let diffs = if staged {
+            self.diff_staged(paths)?
+        } else {
+            self.diff_working_tree(root_path, paths)?
+        };
         
+        self.print_diffs(&diffs);
         Ok(())
     }
     
-    /// Mostra diffs de todos os arquivos não staged
-    pub fn show_all_diffs(&self, root_path: &Path) -> Result<(), CogitError> {
-        let status_list = self.get_status(root_path)?;
-        let mut has_diffs = false;
-        
-        for file_status in status_list {
-            match file_status.status {
-                WorkingTreeStatus::Modified | WorkingTreeStatus::Untracked => {
-                    if has_diffs {
-                        println!(); // Linha em branco entre arquivos
+    /// Imprime uma lista de diffs, ou um aviso se não houver mudanças
+    pub fn print_diffs(&self, diffs: &[FileDiff]) {
+        if diffs.is_empty() {
+            println!("Nenhuma mudança para mostrar");
+        }
+        
+        for diff in diffs {
+            self.print_file_diff(diff);
+        }
+    }
+} 
+
+/// Normaliza um caminho para a forma usada no index e nas árvores
+/// (relativo à raiz, separado por '/', sem prefixo "./")
+pub fn normalize_path(path: &Path) -> String {
+    let normalized = path.to_string_lossy().replace('\\', "/");
+    let mut normalized = normalized.as_str();
+    while let Some(rest) = normalized.strip_prefix("./") {
+        normalized = rest;
+    }
+    normalized.to_string()
+}
+
+/// Detecta conteúdo binário pela presença de bytes nulos no início (heurística do git)
+pub fn is_binary(content: &[u8]) -> bool {
+    const BINARY_CHECK_LEN: usize = 8000;
+    content.iter().take(BINARY_CHECK_LEN).any(|&byte| byte == 0)
+}
+
+/// Verifica se um caminho normalizado corresponde a um filtro (arquivo ou diretório)
+pub fn path_matches(path: &str, filter: &str) -> bool {
+    let filter = normalize_path(Path::new(filter));
+    let filter = filter.trim_end_matches('/');
+    filter.is_empty()
+        || filter == "."
+        || path == filter
+        || path.strip_prefix(filter).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Calcula o script de edição mínimo entre duas sequências (algoritmo de Myers)
+///
+/// Usa a variante em espaço linear: encontra o "middle snake" por busca
+/// bidirecional e resolve recursivamente as duas metades.
+pub fn myers_diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<EditOp> {
+    let mut ops = Vec::with_capacity(old.len().max(new.len()));
+    diff_recursive(old, new, 0, 0, &mut ops);
+    ops
+}
+
+fn diff_recursive<T: PartialEq>(
+    old: &[T],
+    new: &[T],
+    old_offset: usize,
+    new_offset: usize,
+    ops: &mut Vec<EditOp>,
+) {
+    // Prefixo comum
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    for i in 0..prefix {
+        ops.push(EditOp::Equal(old_offset + i, new_offset + i));
+    }
+    let old = &old[prefix..];
+    let new = &new[prefix..];
+    let old_offset = old_offset + prefix;
+    let new_offset = new_offset + prefix;
+
+    // Sufixo comum (emitido no final)
+    let suffix = old.iter().rev().zip(new.iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_middle = &old[..old.len() - suffix];
+    let new_middle = &new[..new.len() - suffix];
+
+    if old_middle.is_empty() {
+        ops.extend((0..new_middle.len()).map(|i| EditOp::Insert(new_offset + i)));
+    } else if new_middle.is_empty() {
+        ops.extend((0..old_middle.len()).map(|i| EditOp::Delete(old_offset + i)));
+    } else {
+        match middle_snake(old_middle, new_middle) {
+            Some((x, y)) => {
+                diff_recursive(&old_middle[..x], &new_middle[..y], old_offset, new_offset, ops);
+                diff_recursive(&old_middle[x..], &new_middle[y..], old_offset + x, new_offset + y, ops);
+            }
+            None => {
+                ops.extend((0..old_middle.len()).map(|i| EditOp::Delete(old_offset + i)));
+                ops.extend((0..new_middle.len()).map(|i| EditOp::Insert(new_offset + i)));
+            }
+        }
+    }
+
+    let old_suffix_start = old_offset + old_middle.len();
+    let new_suffix_start = new_offset + new_middle.len();
+    for i in 0..suffix {
+        ops.push(EditOp::Equal(old_suffix_start + i, new_suffix_start + i));
+    }
+}
+
+/// Busca bidirecional de Myers: retorna o ponto (x, y) onde os caminhos
+/// mínimos de ida e de volta se encontram, ou None se não há nada em comum
+fn middle_snake<T: PartialEq>(old: &[T], new: &[T]) -> Option<(usize, usize)> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let max_d = (n + m + 1) / 2;
+    let v_offset = max_d;
+    let v_length = 2 * max_d + 2;
+    let mut forward = vec![-1isize; v_length as usize];
+    let mut backward = vec![-1isize; v_length as usize];
+    forward[(v_offset + 1) as usize] = 0;
+    backward[(v_offset + 1) as usize] = 0;
+
+    let delta = n - m;
+    // Com delta ímpar a sobreposição é detectada no passo de ida, senão no de volta
+    let check_forward = delta % 2 != 0;
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+
+    for d in 0..max_d {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let k1_offset = (v_offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[k1_offset - 1] < forward[k1_offset + 1]) {
+                forward[k1_offset + 1]
+            } else {
+                forward[k1_offset - 1] + 1
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && old[x1 as usize] == new[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[k1_offset] = x1;
+
+            if x1 > n {
+                k1_end += 2;
+            } else if y1 > m {
+                k1_start += 2;
+            } else if check_forward {
+                let k2_offset = v_offset + delta - k1;
+                if k2_offset >= 0 && k2_offset < v_length && backward[k2_offset as usize] != -1 {
+                    let x2 = n - backward[k2_offset as usize];
+                    if x1 >= x2 {
+                        return Some((x1 as usize, y1 as usize));
                     }
-                    self.show_file_diff(std::path::Path::new(&file_status.file_path))?;
-                    has_diffs = true;
                 }
-                _ => {}
             }
+            k1 += 2;
         }
-        
-        if !has_diffs {
-            println!("Nenhuma mudança para mostrar");
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let k2_offset = (v_offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && backward[k2_offset - 1] < backward[k2_offset + 1]) {
+                backward[k2_offset + 1]
+            } else {
+                backward[k2_offset - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && old[(n - x2 - 1) as usize] == new[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[k2_offset] = x2;
+
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !check_forward {
+                let k1_offset = v_offset + delta - k2;
+                if k1_offset >= 0 && k1_offset < v_length && forward[k1_offset as usize] != -1 {
+                    let x1 = forward[k1_offset as usize];
+                    let y1 = v_offset + x1 - k1_offset;
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
         }
-        
-        Ok(())
     }
-} 
\ No newline at end of file
+
+    None
+}
+
+/// Linha de um hunk; `line` ainda traz a quebra de linha, se houver
+fn diff_line(line_number: usize, line: &str, change_type: LineChangeType) -> DiffLine {
+    DiffLine {
+        line_number,
+        content: line.strip_suffix('\n').unwrap_or(line).to_string(),
+        change_type,
+        no_newline: !line.ends_with('\n'),
+    }
+}
+
+/// Monta um hunk a partir de uma janela contínua do script de edição
+fn build_hunk(ops: &[EditOp], old_lines: &[&str], new_lines: &[&str]) -> DiffHunk {
+    // Posição (base 0) em cada versão onde o hunk começa
+    let (old_position, new_position) = match ops.first() {
+        Some(EditOp::Equal(old, new)) => (*old, *new),
+        Some(EditOp::Delete(old)) => (*old, ops.iter().find_map(|op| match op {
+            EditOp::Equal(_, new) | EditOp::Insert(new) => Some(*new),
+            EditOp::Delete(_) => None,
+        }).unwrap_or(new_lines.len())),
+        Some(EditOp::Insert(new)) => (ops.iter().find_map(|op| match op {
+            EditOp::Equal(old, _) | EditOp::Delete(old) => Some(*old),
+            EditOp::Insert(_) => None,
+        }).unwrap_or(old_lines.len()), *new),
+        None => (0, 0),
+    };
+
+    let mut lines = Vec::with_capacity(ops.len());
+    let mut old_count = 0;
+    let mut new_count = 0;
+
+    for op in ops {
+        match *op {
+            EditOp::Equal(old, _) => {
+                lines.push(diff_line(old + 1, old_lines[old], LineChangeType::Context));
+                old_count += 1;
+                new_count += 1;
+            }
+            EditOp::Delete(old) => {
+                lines.push(diff_line(old + 1, old_lines[old], LineChangeType::Removed));
+                old_count += 1;
+            }
+            EditOp::Insert(new) => {
+                lines.push(diff_line(new + 1, new_lines[new], LineChangeType::Added));
+                new_count += 1;
+            }
+        }
+    }
+
+    // Como no diff unificado, um lado vazio é indicado pela linha anterior ao hunk
+    DiffHunk {
+        old_start: if old_count == 0 { old_position } else { old_position + 1 },
+        old_count,
+        new_start: if new_count == 0 { new_position } else { new_position + 1 },
+        new_count,
+        lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tamanho da maior subsequência comum, por programação dinâmica
+    fn lcs_len<T: PartialEq>(old: &[T], new: &[T]) -> usize {
+        let mut table = vec![vec![0; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                table[i][j] = if old[i] == new[j] {
+                    table[i + 1][j + 1] + 1
+                } else {
+                    table[i + 1][j].max(table[i][j + 1])
+                };
+            }
+        }
+        table[0][0]
+    }
+
+    /// Confere que o script consome as duas sequências em ordem, uma vez cada
+    fn assert_valid_script<T: PartialEq + std::fmt::Debug>(old: &[T], new: &[T], ops: &[EditOp]) {
+        let (mut next_old, mut next_new) = (0, 0);
+        for op in ops {
+            match *op {
+                EditOp::Equal(i, j) => {
+                    assert_eq!((i, j), (next_old, next_new), "{:?} -> {:?}: {:?}", old, new, ops);
+                    assert_eq!(old[i], new[j]);
+                    next_old += 1;
+                    next_new += 1;
+                }
+                EditOp::Delete(i) => {
+                    assert_eq!(i, next_old, "{:?} -> {:?}: {:?}", old, new, ops);
+                    next_old += 1;
+                }
+                EditOp::Insert(j) => {
+                    assert_eq!(j, next_new, "{:?} -> {:?}: {:?}", old, new, ops);
+                    next_new += 1;
+                }
+            }
+        }
+        assert_eq!((next_old, next_new), (old.len(), new.len()));
+    }
+
+    fn equal_count(ops: &[EditOp]) -> usize {
+        ops.iter().filter(|op| matches!(op, EditOp::Equal(..))).count()
+    }
+
+    /// Gerador pseudoaleatório simples (xorshift), para casos reproduzíveis
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn myers_diff_finds_the_classic_minimal_script() {
+        let old: Vec<char> = "abcabba".chars().collect();
+        let new: Vec<char> = "cbabac".chars().collect();
+        let ops = myers_diff(&old, &new);
+
+        assert_valid_script(&old, &new, &ops);
+        assert_eq!(equal_count(&ops), 4);
+        assert_eq!(ops.len() - equal_count(&ops), 5);
+    }
+
+    #[test]
+    fn myers_diff_handles_empty_sides() {
+        assert!(myers_diff::<u8>(&[], &[]).is_empty());
+        assert_eq!(myers_diff(&[], &[1, 2]), vec![EditOp::Insert(0), EditOp::Insert(1)]);
+        assert_eq!(myers_diff(&[1, 2], &[]), vec![EditOp::Delete(0), EditOp::Delete(1)]);
+    }
+
+    #[test]
+    fn myers_diff_matches_lcs_on_random_inputs() {
+        let mut state = 0x2545f4914f6cdd1d;
+        for _ in 0..2000 {
+            let old_len = (next_random(&mut state) % 14) as usize;
+            let new_len = (next_random(&mut state) % 14) as usize;
+            let old: Vec<u64> = (0..old_len).map(|_| next_random(&mut state) % 4).collect();
+            let new: Vec<u64> = (0..new_len).map(|_| next_random(&mut state) % 4).collect();
+
+            let ops = myers_diff(&old, &new);
+            assert_valid_script(&old, &new, &ops);
+            assert_eq!(equal_count(&ops), lcs_len(&old, &new), "{:?} -> {:?}: {:?}", old, new, ops);
+        }
+    }
+
+    #[test]
+    fn middle_snake_meets_on_a_minimal_path() {
+        let old: Vec<char> = "abcabba".chars().collect();
+        let new: Vec<char> = "cbabac".chars().collect();
+        let (x, y) = middle_snake(&old, &new).expect("as sequências têm linhas em comum");
+
+        // Cada metade resolvida separadamente ainda soma um script mínimo
+        assert_eq!(lcs_len(&old[..x], &new[..y]) + lcs_len(&old[x..], &new[y..]), lcs_len(&old, &new));
+    }
+
+    #[test]
+    fn myers_diff_replaces_everything_without_common_lines() {
+        let ops = myers_diff(&['a', 'b'], &['c', 'd', 'e']);
+        assert_eq!(equal_count(&ops), 0);
+        assert_eq!(ops.len(), 5);
+    }
+}
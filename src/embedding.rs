@@ -5,6 +5,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cogit::CogitError;
+use crate::lockfile::write_atomic;
 
 /// Configuração para API OpenAI
 #[derive(Debug, Clone)]
@@ -64,6 +65,7 @@ struct EmbeddingRequest {
 #[derive(Debug, Deserialize)]
 struct EmbeddingResponse {
     data: Vec<EmbeddingData>,
+    #[allow(dead_code)] // Mantido para futura contagem real de tokens
     usage: Usage,
 }
 
@@ -73,6 +75,7 @@ struct EmbeddingData {
 }
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 struct Usage {
     total_tokens: u32,
 }
@@ -136,14 +139,13 @@ impl EmbeddingEngine {
     pub fn analyze_modified_files(&self, root_path: &Path) -> Result<Vec<PathBuf>, CogitError> {
         let mut valid_files = Vec::new();
         
-        // Por enquanto, vamos analisar todos os arquivos no diretório raiz
+        // Por enquanto, vamos analisar todos os arquivos do working tree
         // Em uma implementação futura, isso seria baseado em git diff
-        for entry in fs::read_dir(root_path)? {
-            let entry = entry?;
-            let path = entry.path();
+        for relative in crate::cogit::CogitRepository::walk_working_tree(root_path)? {
+            let path = root_path.join(relative);
             
-            // Filtrar apenas arquivos de código válidos
-            if self.is_code_file(&path) {
+            // Filtrar apenas arquivos de código válidos (binários não são enviados à API)
+            if self.is_code_file(&path) && !fs::read(&path).is_ok_and(|content| crate::diff::is_binary(&content)) {
                 valid_files.push(path);
             }
         }
@@ -157,26 +159,7 @@ impl EmbeddingEngine {
             return false;
         }
         
-        let file_name = path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("");
-        
-        // Ignorar arquivos ocultos e diretório .cogit
-        if file_name.starts_with('.') {
-            return false;
-        }
-        
-        // Ignorar apenas arquivos de documentação específicos (manter README.md para IA)
-        let ignored_files = [
-            "CHANGELOG.md", "LICENSE", "LICENSE.txt",
-            "CONTRIBUTING.md", "CODE_OF_CONDUCT.md", "SECURITY.md",
-            "GUIA_DESENVOLVIMENTO.md", "CONTEXTO_CHATGPT.md", 
-            "TESTE_FUNCIONALIDADES.md", "STATUS_SEMINARIO.md"
-        ];
-        
-        if ignored_files.contains(&file_name) {
-            return false;
-        }
+        // Arquivos ignorados (.cogitignore) já foram filtrados por walk_working_tree
         
         // Lista de extensões válidas para análise IA (código + documentação relevante)
         let code_extensions = [
@@ -196,12 +179,18 @@ impl EmbeddingEngine {
     
     /// Gera embedding para um arquivo usando OpenAI API
     pub async fn generate_file_embedding(&self, file_path: &Path) -> Result<FileEmbedding, CogitError> {
-        // Ler conteúdo do arquivo
-        let content = fs::read_to_string(file_path)
-            .map_err(|e| CogitError::IoError(e))?;
+        // Ler conteúdo do arquivo (bytes brutos: o hash deve bater com o do object store)
+        let bytes = fs::read(file_path)?;
+        if crate::diff::is_binary(&bytes) {
+            return Err(CogitError::IoError(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Arquivo binário não pode ser analisado",
+            )));
+        }
+        let content = String::from_utf8_lossy(&bytes);
         
         // Calcular hash do conteúdo
-        let content_hash = crate::cogit::CogitRepository::calculate_hash(content.as_bytes());
+        let content_hash = crate::cogit::CogitRepository::calculate_hash(&bytes);
         
         // Gerar embedding via OpenAI
         let embedding_vector = self.call_openai_embedding(&content).await?;
@@ -237,21 +226,20 @@ impl EmbeddingEngine {
         };
         
         let response = self.client
-            .post(&format!("{}/embeddings", self.config.base_url))
+            .post(format!("{}/embeddings", self.config.base_url))
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
             .await
             .map_err(|e| CogitError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+                std::io::Error::other(e.to_string())
             ))?;
         
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Erro desconhecido".to_string());
             return Err(CogitError::IoError(
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                std::io::Error::other(
                     format!("Erro da API OpenAI: {}", error_text)
                 )
             ));
@@ -329,7 +317,7 @@ impl EmbeddingEngine {
             .join(format!("{}.json", index.commit_hash));
         
         let json_content = serde_json::to_string_pretty(index)?;
-        fs::write(index_path, json_content)?;
+        write_atomic(&index_path, json_content.as_bytes())?;
         
         Ok(())
     }
@@ -364,7 +352,7 @@ impl EmbeddingEngine {
             let entry = entry?;
             let path = entry.path();
             
-            if path.is_file() && path.extension().map_or(false, |e| e == "json") {
+            if path.is_file() && path.extension().is_some_and(|e| e == "json") {
                 if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
                     commits.push(stem.to_string());
                 }
@@ -472,21 +460,20 @@ impl EmbeddingEngine {
         };
         
         let response = self.client
-            .post(&format!("{}/chat/completions", self.config.base_url))
+            .post(format!("{}/chat/completions", self.config.base_url))
             .header("Authorization", format!("Bearer {}", self.config.api_key))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
             .await
             .map_err(|e| CogitError::IoError(
-                std::io::Error::new(std::io::ErrorKind::Other, e.to_string())
+                std::io::Error::other(e.to_string())
             ))?;
         
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Erro desconhecido".to_string());
             return Err(CogitError::IoError(
-                std::io::Error::new(
-                    std::io::ErrorKind::Other,
+                std::io::Error::other(
                     format!("Erro da API OpenAI: {}", error_text)
                 )
             ));
@@ -0,0 +1,158 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::cogit::{CogitError, CogitRepository, Commit, TreeEntry};
+use crate::object::ObjectType;
+use crate::tag::Tag;
+
+/// Bits do código de saída do `cogit fsck` (combinados quando há mais de um problema)
+pub const FSCK_DANGLING: i32 = 1;  // Objetos inalcançáveis a partir das referências
+pub const FSCK_MISSING: i32 = 2;   // Objetos referenciados que não existem
+pub const FSCK_CORRUPT: i32 = 4;   // Objetos ou referências corrompidos
+pub const FSCK_FAILED: i32 = 8;    // A verificação não pôde ser concluída
+
+/// Resultado de uma verificação de integridade
+#[derive(Debug, Clone, Default)]
+pub struct FsckReport {
+    pub checked: usize,                         // Objetos lidos e validados
+    pub corrupt: Vec<(String, String)>,         // Objeto ou referência -> motivo
+    pub missing: Vec<(String, ObjectType, String)>, // Hash, tipo esperado, quem referencia
+    pub dangling: Vec<(String, ObjectType)>,
+}
+
+impl FsckReport {
+    /// Código de saída correspondente aos problemas encontrados (0 se nenhum)
+    pub fn exit_code(&self) -> i32 {
+        let mut code = 0;
+        if !self.dangling.is_empty() {
+            code |= FSCK_DANGLING;
+        }
+        if !self.missing.is_empty() {
+            code |= FSCK_MISSING;
+        }
+        if !self.corrupt.is_empty() {
+            code |= FSCK_CORRUPT;
+        }
+        code
+    }
+}
+
+impl CogitRepository {
+    /// Verifica a integridade do object store e do grafo de objetos
+    ///
+    /// Todo objeto armazenado é lido e conferido contra o seu hash; commits e
+    /// árvores precisam ter JSON válido. Em seguida o grafo é percorrido a
+    /// partir do HEAD, das referências e do index, acusando objetos ausentes,
+    /// de tipo errado e os que ficaram inalcançáveis.
+    pub fn fsck(&self) -> Result<FsckReport, CogitError> {
+        let mut report = FsckReport::default();
+
+        let mut stored = self.loose_objects()?;
+        stored.extend(self.packed_objects()?);
+        stored.sort();
+        stored.dedup();
+
+        // Objeto válido -> (tipo, objetos que ele referencia)
+        let mut objects: BTreeMap<String, (ObjectType, Vec<(String, ObjectType)>)> = BTreeMap::new();
+        let mut corrupt = BTreeSet::new();
+
+        for hash in stored {
+            match self.read_object(&hash).and_then(|(object_type, content)| {
+                Ok((object_type, object_links(object_type, &content)?))
+            }) {
+                Ok(object) => {
+                    objects.insert(hash, object);
+                }
+                Err(e) => {
+                    let reason = match e {
+                        CogitError::CorruptObject(_, reason) => reason,
+                        other => other.to_string(),
+                    };
+                    report.corrupt.push((hash.clone(), reason));
+                    corrupt.insert(hash);
+                }
+            }
+        }
+        report.checked = objects.len() + corrupt.len();
+
+        let (mut pending, invalid_refs) = self.reachability_roots()?;
+        report.corrupt.extend(invalid_refs);
+
+        // Visitas por (hash, tipo esperado): um mesmo hash referenciado como
+        // blob e como árvore precisa ser conferido para os dois tipos
+        let mut visited = HashSet::new();
+        let mut reachable = BTreeSet::new();
+        while let Some((hash, expected, referrer)) = pending.pop() {
+            if !visited.insert((hash.clone(), expected)) {
+                continue;
+            }
+            reachable.insert(hash.clone());
+            if corrupt.contains(&hash) {
+                continue;
+            }
+
+            let Some((object_type, links)) = objects.get(&hash) else {
+                report.missing.push((hash, expected, referrer));
+                continue;
+            };
+
+            let links = if *object_type == expected {
+                links.clone()
+            } else if self.is_legacy_object(&hash)? {
+                // O tipo de um objeto legado é só um palpite: vale o esperado
+                let (_, content) = self.read_object(&hash)?;
+                match object_links(expected, &content) {
+                    Ok(links) => links,
+                    Err(e) => {
+                        report.corrupt.push((hash, e.to_string()));
+                        continue;
+                    }
+                }
+            } else {
+                report.corrupt.push((
+                    hash.clone(),
+                    format!("{} esperado por {}, encontrado {}", expected, referrer, object_type),
+                ));
+                continue;
+            };
+
+            for (link, link_type) in links {
+                pending.push((link, link_type, hash.clone()));
+            }
+        }
+
+        report.dangling = objects
+            .iter()
+            .filter(|(hash, _)| !reachable.contains(*hash))
+            .map(|(hash, (object_type, _))| (hash.clone(), *object_type))
+            .collect();
+
+        Ok(report)
+    }
+}
+
+/// Objetos referenciados por um commit, árvore ou tag (validando o JSON)
+fn object_links(object_type: ObjectType, content: &[u8]) -> Result<Vec<(String, ObjectType)>, CogitError> {
+    match object_type {
+        ObjectType::Blob => Ok(Vec::new()),
+        ObjectType::Tree => {
+            let entries: Vec<TreeEntry> = serde_json::from_slice(content)?;
+            Ok(entries
+                .into_iter()
+                .map(|entry| {
+                    let entry_type = if entry.is_file { ObjectType::Blob } else { ObjectType::Tree };
+                    (entry.hash, entry_type)
+                })
+                .collect())
+        }
+        ObjectType::Commit => {
+            let commit: Commit = serde_json::from_slice(content)?;
+            let mut links = vec![(commit.tree_hash, ObjectType::Tree)];
+            links.extend(commit.parents.into_iter().map(|parent| (parent, ObjectType::Commit)));
+            Ok(links)
+        }
+        ObjectType::Tag => {
+            let tag: Tag = serde_json::from_slice(content)?;
+            Ok(vec![(tag.object, tag.object_type)])
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::DiffEngine;
+use crate::object::{encode_object, ObjectType};
+use crate::pack::RepackStats;
+use crate::refs::{is_full_hash, Head};
+use crate::tag::TAGS_PREFIX;
+
+/// Prazo padrão para remover objetos inalcançáveis (o mesmo do git)
+pub const DEFAULT_PRUNE_EXPIRY: &str = "14d";
+
+/// A partir de quando um objeto inalcançável pode ser removido
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneExpiry {
+    Never,                  // --prune=never: nada é removido
+    OlderThan(Duration),    // --prune=<idade>, "now" equivale a zero
+}
+
+/// Interpreta o valor de `--prune`: `now`, `never` ou um número com unidade
+/// (`s`, `m`, `h`, `d`, `w`), como `30m` ou `2w`
+pub fn parse_prune_expiry(value: &str) -> Result<PruneExpiry, String> {
+    let value = value.trim();
+    match value {
+        "now" => return Ok(PruneExpiry::OlderThan(Duration::ZERO)),
+        "never" => return Ok(PruneExpiry::Never),
+        _ => {}
+    }
+
+    let invalid = || format!("prazo inválido '{}' (use now, never ou algo como 30m, 12h, 14d, 2w)", value);
+    let unit_start = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let amount: u64 = value[..unit_start].parse().map_err(|_| invalid())?;
+    let seconds = match &value[unit_start..] {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    Ok(PruneExpiry::OlderThan(Duration::from_secs(amount.saturating_mul(seconds))))
+}
+
+/// Raiz da alcançabilidade: hash, tipo esperado e origem (referência, HEAD ou index)
+pub type ReachabilityRoot = (String, ObjectType, String);
+
+/// Referência que não pôde ser lida: nome e motivo
+pub type InvalidRef = (String, String);
+
+/// Resultado de uma coleta de lixo
+#[derive(Debug, Clone)]
+pub struct GcStats {
+    pub pruned_objects: usize,   // Objetos inalcançáveis removidos
+    pub kept_recent: usize,      // Inalcançáveis mantidos por serem recentes
+    pub pruned_indexes: usize,   // Índices de embeddings removidos
+    pub repack: RepackStats,
+}
+
+impl CogitRepository {
+    /// Pontos de partida da alcançabilidade: referências, HEAD destacado, merge em
+    /// andamento, reflogs e index
+    ///
+    /// Também retorna as referências inválidas encontradas pelo caminho.
+    pub fn reachability_roots(&self) -> Result<(Vec<ReachabilityRoot>, Vec<InvalidRef>), CogitError> {
+        let mut roots = Vec::new();
+        let mut invalid = Vec::new();
+
+        for (name, value) in self.list_refs()? {
+            if is_full_hash(&value) {
+                // Tags podem apontar para um objeto de tag anotada em vez de um commit
+                let object_type = match name.starts_with(TAGS_PREFIX) {
+                    true => self.read_object(&value).map_or(ObjectType::Commit, |(object_type, _)| object_type),
+                    false => ObjectType::Commit,
+                };
+                roots.push((value, object_type, name));
+            } else {
+                invalid.push((name, format!("referência inválida '{}'", value)));
+            }
+        }
+
+        match self.read_head() {
+            Ok(Head::Detached(hash)) => roots.push((hash, ObjectType::Commit, "HEAD".to_string())),
+            Ok(Head::Branch(_)) => {}
+            Err(e) => invalid.push(("HEAD".to_string(), e.to_string())),
+        }
+
+        // Commit sendo integrado por um merge ainda não concluído
+        for hash in self.merge_heads()? {
+            if is_full_hash(&hash) {
+                roots.push((hash, ObjectType::Commit, "MERGE_HEAD".to_string()));
+            } else {
+                invalid.push(("MERGE_HEAD".to_string(), format!("referência inválida '{}'", hash)));
+            }
+        }
+
+        // Commit original de um cherry-pick ou revert parado
+        if let Some(kind) = self.pick_in_progress() {
+            if let Some(hash) = self.pick_head(kind)? {
+                if is_full_hash(&hash) {
+                    roots.push((hash, ObjectType::Commit, kind.state_file().to_string()));
+                } else {
+                    invalid.push((kind.state_file().to_string(), format!("referência inválida '{}'", hash)));
+                }
+            }
+        }
+
+        // Valores antigos e novos dos reflogs: permitem recuperar commits
+        // abandonados por um reset ou por um branch removido do HEAD
+        for ref_name in self.list_reflogs()? {
+            let origin = format!("reflog {}", ref_name);
+            for entry in self.read_reflog(&ref_name)? {
+                for hash in entry.old.into_iter().chain([entry.new]) {
+                    if is_full_hash(&hash) {
+                        roots.push((hash, ObjectType::Commit, origin.clone()));
+                    } else {
+                        invalid.push((origin.clone(), format!("referência inválida '{}'", hash)));
+                    }
+                }
+            }
+        }
+
+        let staging_area = DiffEngine::new(self.cogit_dir().to_path_buf()).load_staging_area()?;
+        for entry in staging_area.entries.values().filter(|entry| !entry.deleted) {
+            roots.push((entry.content_hash.clone(), ObjectType::Blob, "index".to_string()));
+        }
+
+        Ok((roots, invalid))
+    }
+
+    /// Conjunto de objetos alcançáveis a partir das raízes
+    ///
+    /// Falha se alguma referência for inválida ou algum objeto estiver ausente:
+    /// nesse estado não é seguro decidir o que pode ser removido.
+    pub fn reachable_objects(&self) -> Result<BTreeSet<String>, CogitError> {
+        let (roots, invalid) = self.reachability_roots()?;
+        if let Some((name, reason)) = invalid.into_iter().next() {
+            return Err(CogitError::InvalidRefName(format!("{}: {}", name, reason)));
+        }
+
+        let mut reachable = BTreeSet::new();
+        let mut pending: Vec<(String, ObjectType)> = roots
+            .into_iter()
+            .map(|(hash, object_type, _)| (hash, object_type))
+            .collect();
+
+        while let Some((hash, object_type)) = pending.pop() {
+            if !reachable.insert(hash.clone()) {
+                continue;
+            }
+
+            match object_type {
+                ObjectType::Commit => {
+                    let commit = self.load_commit(&hash)?;
+                    pending.push((commit.tree_hash, ObjectType::Tree));
+                    pending.extend(commit.parents.into_iter().map(|parent| (parent, ObjectType::Commit)));
+                }
+                ObjectType::Tree => {
+                    for entry in self.load_tree(&hash)? {
+                        let entry_type = if entry.is_file { ObjectType::Blob } else { ObjectType::Tree };
+                        pending.push((entry.hash, entry_type));
+                    }
+                }
+                ObjectType::Tag => {
+                    let tag = self.load_tag(&hash)?;
+                    pending.push((tag.object, tag.object_type));
+                }
+                ObjectType::Blob => {
+                    if !self.has_object(&hash)? {
+                        return Err(CogitError::CorruptObject(hash, "objeto referenciado não existe".to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok(reachable)
+    }
+
+    /// Remove objetos inalcançáveis mais antigos que o prazo, reempacota os
+    /// alcançáveis e apaga índices de embeddings de commits que deixaram de existir
+    ///
+    /// Objetos inalcançáveis ainda dentro do prazo continuam soltos (os que
+    /// estavam em packs são extraídos), para que uma operação em andamento não
+    /// perca dados; a idade de um objeto empacotado é a do seu pack.
+    pub fn gc(&self, expiry: PruneExpiry) -> Result<GcStats, CogitError> {
+        let reachable = self.reachable_objects()?;
+        let now = SystemTime::now();
+        let expired = |modified: SystemTime| match expiry {
+            PruneExpiry::Never => false,
+            PruneExpiry::OlderThan(age) => now.duration_since(modified).unwrap_or_default() >= age,
+        };
+
+        let mut pruned_objects = 0;
+        let mut kept_recent = 0;
+
+        let loose: BTreeSet<String> = self.loose_objects()?.into_iter().collect();
+        for hash in loose.iter().filter(|hash| !reachable.contains(*hash)) {
+            let object_path = self.loose_object_path(hash);
+            if expired(fs::metadata(&object_path)?.modified()?) {
+                fs::remove_file(&object_path)?;
+                if let Some(object_dir) = object_path.parent() {
+                    let _ = fs::remove_dir(object_dir);
+                }
+                pruned_objects += 1;
+            } else {
+                kept_recent += 1;
+            }
+        }
+
+        for hash in self.packed_objects()? {
+            if reachable.contains(&hash) || loose.contains(&hash) {
+                continue;
+            }
+
+            let (pack_path, _) = self.packed_entry(&hash)?.ok_or(CogitError::InvalidHash)?;
+            if expired(fs::metadata(&pack_path)?.modified()?) {
+                pruned_objects += 1;
+                continue;
+            }
+
+            // Extrai o objeto para o armazenamento solto antes que o repack o descarte
+            let (object_type, content) = self.read_object(&hash)?;
+            let data = match self.is_legacy_object(&hash)? {
+                true => content,
+                false => encode_object(object_type, &content)?,
+            };
+            self.write_loose_object(&hash, &data)?;
+            kept_recent += 1;
+        }
+
+        let repack = self.repack(Some(&reachable))?;
+
+        let mut pruned_indexes = 0;
+        let index_dir = self.cogit_dir().join("index");
+        if index_dir.is_dir() {
+            for entry in fs::read_dir(&index_dir)? {
+                let path = entry?.path();
+                let commit_hash = match path.file_stem().and_then(|stem| stem.to_str()) {
+                    Some(stem) if is_full_hash(stem) && path.extension().and_then(|ext| ext.to_str()) == Some("json") => {
+                        stem.to_string()
+                    }
+                    _ => continue,
+                };
+
+                if !reachable.contains(&commit_hash) && !self.has_object(&commit_hash)? {
+                    fs::remove_file(&path)?;
+                    pruned_indexes += 1;
+                }
+            }
+        }
+
+        Ok(GcStats { pruned_objects, kept_recent, pruned_indexes, repack })
+    }
+}
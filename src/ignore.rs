@@ -0,0 +1,336 @@
+use std::fs;
+use std::path::Path;
+
+use crate::cogit::CogitError;
+
+/// Nome dos arquivos de regras de ignore (um por diretório, como o .gitignore)
+pub const IGNORE_FILE: &str = ".cogitignore";
+
+/// Diretórios internos que nunca são versionados, independente das regras
+const ALWAYS_IGNORED: [&str; 2] = [".cogit", ".git"];
+
+/// Regras padrão, com prioridade mais baixa: arquivos ocultos ficam de fora
+/// (podem ser reincluídos com `!` em um .cogitignore), exceto o próprio .cogitignore
+const DEFAULT_PATTERNS: [&str; 2] = [".*", "!.cogitignore"];
+
+/// Uma linha de um arquivo .cogitignore
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    base: String,      // Diretório do .cogitignore, relativo à raiz ("" = raiz)
+    pattern: String,   // Glob sem '!', '/' inicial e '/' final
+    negated: bool,     // "!padrão" reinclui caminhos
+    dir_only: bool,    // "padrão/" só vale para diretórios
+    anchored: bool,    // Padrões com '/' são relativos ao diretório do arquivo
+}
+
+/// Conjunto de regras de ignore de um working tree
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreRules {
+    /// Carrega as regras padrão e todos os .cogitignore do working tree
+    ///
+    /// Diretórios ignorados não são visitados, então arquivos de regras dentro
+    /// deles não têm efeito (mesmo comportamento do git).
+    pub fn load(root: &Path) -> Result<Self, CogitError> {
+        let mut rules = Self::default();
+        for pattern in DEFAULT_PATTERNS {
+            rules.add_pattern("", pattern);
+        }
+        rules.load_dir(root, "")?;
+        Ok(rules)
+    }
+
+    fn load_dir(&mut self, dir: &Path, prefix: &str) -> Result<(), CogitError> {
+        let ignore_file = dir.join(IGNORE_FILE);
+        if ignore_file.is_file() {
+            let content = fs::read_to_string(&ignore_file)?;
+            for line in content.lines() {
+                self.add_pattern(prefix, line);
+            }
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = join_path(prefix, &name);
+            if !self.is_ignored(&relative, true) {
+                self.load_dir(&path, &relative)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Interpreta uma linha no formato do .gitignore
+    fn add_pattern(&mut self, base: &str, line: &str) {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        if pattern.is_empty() {
+            return;
+        }
+
+        self.rules.push(IgnoreRule {
+            base: base.to_string(),
+            pattern: pattern.to_string(),
+            negated,
+            dir_only,
+            anchored,
+        });
+    }
+
+    /// Verifica se um caminho relativo (separado por '/') deve ser ignorado
+    ///
+    /// Um caminho dentro de um diretório ignorado é sempre ignorado.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+        if components.iter().any(|c| ALWAYS_IGNORED.contains(c)) {
+            return true;
+        }
+
+        for depth in 1..components.len() {
+            if self.matches(&components[..depth].join("/"), true) {
+                return true;
+            }
+        }
+
+        self.matches(&components.join("/"), is_dir)
+    }
+
+    /// Avalia as regras para um único caminho: a última regra que casa decide
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+
+            let relative = if rule.base.is_empty() {
+                path
+            } else {
+                match path.strip_prefix(&rule.base).and_then(|rest| rest.strip_prefix('/')) {
+                    Some(rest) => rest,
+                    None => continue,
+                }
+            };
+
+            let candidate = if rule.anchored {
+                relative
+            } else {
+                relative.rsplit('/').next().unwrap_or(relative)
+            };
+
+            if glob_match(&rule.pattern, candidate) {
+                ignored = !rule.negated;
+            }
+        }
+
+        ignored
+    }
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+/// Casa um glob no estilo do gitignore: `*` e `?` não atravessam '/',
+/// `**` atravessa diretórios e `[...]` define classes de caracteres
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            match rest.first() {
+                // "**" no final casa com tudo
+                None => true,
+                // "**/" casa com zero ou mais diretórios
+                Some('/') => {
+                    let rest = &rest[1..];
+                    (0..=text.len())
+                        .filter(|&i| i == 0 || text[i - 1] == '/')
+                        .any(|i| glob_match_from(rest, &text[i..]))
+                }
+                _ => (0..=text.len()).any(|i| glob_match_from(rest, &text[i..])),
+            }
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            let limit = text.iter().position(|&c| c == '/').unwrap_or(text.len());
+            (0..=limit).any(|i| glob_match_from(rest, &text[i..]))
+        }
+        Some('?') => {
+            !text.is_empty() && text[0] != '/' && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some('[') => match (text.first(), parse_class(&pattern[1..])) {
+            (Some(&c), Some((matched, consumed))) => {
+                c != '/' && matched(c) && glob_match_from(&pattern[1 + consumed..], &text[1..])
+            }
+            // Classe sem ']' de fechamento: '[' literal
+            (Some(&c), None) => c == '[' && glob_match_from(&pattern[1..], &text[1..]),
+            (None, _) => false,
+        },
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && glob_match_from(&pattern[2..], &text[1..])
+        }
+        Some(&c) => text.first() == Some(&c) && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+/// Interpreta uma classe `[...]` (sem o '[' inicial); retorna o predicado e
+/// quantos caracteres do padrão foram consumidos, incluindo o ']'
+fn parse_class(pattern: &[char]) -> Option<(impl Fn(char) -> bool, usize)> {
+    let mut idx = 0;
+    let negated = matches!(pattern.first(), Some('!') | Some('^'));
+    if negated {
+        idx += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let start = idx;
+    while idx < pattern.len() && (pattern[idx] != ']' || idx == start) {
+        let low = pattern[idx];
+        if idx + 2 < pattern.len() && pattern[idx + 1] == '-' && pattern[idx + 2] != ']' {
+            ranges.push((low, pattern[idx + 2]));
+            idx += 3;
+        } else {
+            ranges.push((low, low));
+            idx += 1;
+        }
+    }
+
+    if idx >= pattern.len() {
+        return None;
+    }
+
+    let matcher = move |c: char| ranges.iter().any(|&(low, high)| low <= c && c <= high) != negated;
+    Some((matcher, idx + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Regras padrão seguidas das linhas dadas, como em um .cogitignore em `base`
+    fn rules(base: &str, lines: &[&str]) -> IgnoreRules {
+        let mut rules = IgnoreRules::default();
+        for pattern in DEFAULT_PATTERNS {
+            rules.add_pattern("", pattern);
+        }
+        for line in lines {
+            rules.add_pattern(base, line);
+        }
+        rules
+    }
+
+    #[test]
+    fn glob_wildcards_do_not_cross_directories() {
+        assert!(glob_match("*.log", "debug.log"));
+        assert!(!glob_match("*.log", "logs/debug.log"));
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file10.txt"));
+        assert!(!glob_match("a?b", "a/b"));
+    }
+
+    #[test]
+    fn glob_double_star_matches_any_depth() {
+        assert!(glob_match("**/build", "build"));
+        assert!(glob_match("**/build", "a/b/build"));
+        assert!(glob_match("docs/**", "docs/a/b.md"));
+        assert!(glob_match("a/**/z", "a/z"));
+        assert!(glob_match("a/**/z", "a/b/c/z"));
+        assert!(!glob_match("a/**/z", "ab/z"));
+    }
+
+    #[test]
+    fn glob_character_classes() {
+        assert!(glob_match("[abc].rs", "b.rs"));
+        assert!(!glob_match("[abc].rs", "d.rs"));
+        assert!(glob_match("v[0-9]", "v7"));
+        assert!(glob_match("[!0-9]x", "ax"));
+        assert!(!glob_match("[!0-9]x", "1x"));
+        assert!(glob_match("[x", "[x"));
+        assert!(glob_match("\\*", "*"));
+        assert!(!glob_match("\\*", "a"));
+    }
+
+    #[test]
+    fn later_negation_reincludes_a_path() {
+        let rules = rules("", &["*.log", "!keep.log"]);
+        assert!(rules.is_ignored("debug.log", false));
+        assert!(!rules.is_ignored("keep.log", false));
+        assert!(!rules.is_ignored("sub/keep.log", false));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = rules("", &["!keep.log", "*.log"]);
+        assert!(rules.is_ignored("keep.log", false));
+    }
+
+    #[test]
+    fn negation_cannot_reinclude_inside_ignored_directory() {
+        let rules = rules("", &["build/", "!build/keep.txt"]);
+        assert!(rules.is_ignored("build", true));
+        assert!(rules.is_ignored("build/keep.txt", false));
+        assert!(!rules.is_ignored("build", false));
+    }
+
+    #[test]
+    fn anchored_patterns_are_relative_to_their_file() {
+        let rules = rules("src", &["/gen", "tmp/*.o"]);
+        assert!(rules.is_ignored("src/gen", false));
+        assert!(!rules.is_ignored("src/sub/gen", false));
+        assert!(!rules.is_ignored("gen", false));
+        assert!(rules.is_ignored("src/tmp/a.o", false));
+        assert!(!rules.is_ignored("tmp/a.o", false));
+    }
+
+    #[test]
+    fn hidden_files_are_ignored_by_default_except_the_rules_file() {
+        let rules = rules("", &["!.env.example"]);
+        assert!(rules.is_ignored(".env", false));
+        assert!(rules.is_ignored(".cogit/HEAD", false));
+        assert!(!rules.is_ignored(".cogitignore", false));
+        assert!(!rules.is_ignored(".env.example", false));
+    }
+
+    #[test]
+    fn comments_blank_lines_and_escapes() {
+        let rules = rules("", &["# comentário", "", "\\!importante", "\\#hash"]);
+        assert!(!rules.is_ignored("# comentário", false));
+        assert!(rules.is_ignored("!importante", false));
+        assert!(rules.is_ignored("#hash", false));
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::cogit::CogitError;
+
+/// Sufixo dos arquivos de lock (mesma convenção do git)
+pub const LOCK_SUFFIX: &str = ".lock";
+
+/// Contador para nomes de arquivos temporários únicos dentro do processo
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Grava um arquivo de forma atômica: o conteúdo vai para um temporário no
+/// mesmo diretório, que é sincronizado e renomeado sobre o destino
+///
+/// Leitores veem o arquivo antigo ou o novo completo, nunca um arquivo truncado.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), CogitError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(
+        ".{}.tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = write_and_sync(&temp_path, content).and_then(|_| Ok(fs::rename(&temp_path, path)?));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_and_sync(path: &Path, content: &[u8]) -> Result<(), CogitError> {
+    let mut file = File::create(path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Lock exclusivo sobre um arquivo do repositório (index, HEAD, refs)
+///
+/// O lock é o arquivo `<caminho>.lock`, criado de forma exclusiva: se ele já
+/// existe, outro processo está alterando o arquivo. O novo conteúdo é gravado
+/// no próprio arquivo de lock e renomeado sobre o destino em `commit`; se o
+/// lock for descartado sem commit, o destino fica intacto.
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    lock_path: PathBuf,
+    file: File,
+    committed: bool,
+}
+
+impl LockFile {
+    /// Adquire o lock de um arquivo, falhando com `LockHeld` se já estiver em uso
+    pub fn acquire(path: &Path) -> Result<Self, CogitError> {
+        let mut lock_name = path.as_os_str().to_os_string();
+        lock_name.push(LOCK_SUFFIX);
+        let lock_path = PathBuf::from(lock_name);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                return Err(CogitError::LockHeld(lock_path.display().to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self { path: path.to_path_buf(), lock_path, file, committed: false })
+    }
+
+    /// Grava o novo conteúdo e o publica no destino, liberando o lock
+    pub fn commit(mut self, content: &[u8]) -> Result<(), CogitError> {
+        self.file.write_all(content)?;
+        self.file.sync_all()?;
+        fs::rename(&self.lock_path, &self.path)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // Lock abandonado (erro ou sem commit): remove o arquivo de lock
+        if !self.committed {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
@@ -2,10 +2,26 @@ use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
 mod cogit;
+mod config;
 mod embedding;
 mod diff;
+mod fsck;
+mod gc;
+mod ignore;
+mod lockfile;
+mod merge;
+mod object;
+mod pack;
+mod reflog;
+mod refs;
+mod reset;
+mod revision;
+mod sequencer;
+mod stash;
+mod tag;
+mod worktree;
 
-use cogit::CogitRepository;
+use cogit::{CogitError, CogitRepository};
 use embedding::EmbeddingEngine;
 use diff::DiffEngine;
 
@@ -26,37 +42,217 @@ enum Commands {
         #[arg(default_value = ".")]
         path: PathBuf,
     },
+    /// Lê ou define uma configuração (ex.: user.name, user.email)
+    Config {
+        /// Chave no formato secao.nome
+        key: String,
+        /// Novo valor (omita para apenas ler)
+        value: Option<String>,
+        /// Usa a configuração do usuário (~/.cogitconfig.json) em vez da do repositório
+        #[arg(long)]
+        global: bool,
+    },
     /// Adiciona arquivos ao staging area
     Add {
         /// Arquivos para adicionar (use "." para todos)
         #[arg(default_value = ".")]
         files: String,
+        /// Adiciona o arquivo mesmo que ele seja ignorado por um .cogitignore
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Remove arquivos do working tree e registra a remoção no staging area
+    Rm {
+        /// Arquivos ou diretórios a remover
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Apenas registra a remoção, mantendo o arquivo no disco
+        #[arg(long)]
+        cached: bool,
+        /// Remove mesmo com mudanças não commitadas
+        #[arg(short, long)]
+        force: bool,
     },
     /// Mostra diferenças entre versões de arquivos
     Diff {
+        /// Commits a comparar: <rev> compara com o working tree, <rev1> <rev2> compara os dois
+        #[arg(num_args = 0..=2)]
+        revs: Vec<String>,
         /// Arquivo específico para mostrar diff (opcional)
         #[arg(long)]
         file: Option<String>,
-        /// Mostrar diff do staging area vs working tree
+        /// Mostrar diff do staging area vs HEAD (padrão: working tree vs staging area)
         #[arg(long, default_value = "false")]
         staged: bool,
+        /// Limitar o diff a estes caminhos (após "--")
+        #[arg(last = true)]
+        paths: Vec<String>,
     },
     /// Cria um novo commit com as mudanças atuais
     Commit {
-        /// Mensagem do commit
+        /// Mensagem do commit (opcional ao concluir um merge)
         #[arg(short, long)]
-        message: String,
+        message: Option<String>,
         /// Pular análise de embeddings IA (modo rápido)
         #[arg(long, default_value = "false")]
         skip_ai: bool,
     },
     /// Mostra o histórico de commits
     Log,
+    /// Mostra os movimentos do HEAD ou de um branch (commits, checkouts, merges...)
+    Reflog {
+        /// Referência (padrão: HEAD)
+        #[arg(default_value = "HEAD")]
+        reference: String,
+    },
     /// Mostra o status atual do repositório
     Status,
+    /// Lista, cria ou remove branches
+    Branch {
+        /// Nome do branch a criar (ou remover, com -d/-D)
+        name: Option<String>,
+        /// Commit ou branch de partida (padrão: HEAD)
+        start_point: Option<String>,
+        /// Remove o branch (apenas se já integrado ao HEAD)
+        #[arg(short = 'd', long = "delete")]
+        delete: bool,
+        /// Remove o branch mesmo que não esteja integrado
+        #[arg(short = 'D')]
+        force_delete: bool,
+        /// Lista os branches
+        #[arg(short = 'l', long = "list")]
+        list: bool,
+    },
+    /// Lista, cria ou remove tags
+    Tag {
+        /// Nome da tag a criar (ou remover, com -d; com -l, um glob para filtrar)
+        name: Option<String>,
+        /// Commit marcado (padrão: HEAD)
+        rev: Option<String>,
+        /// Cria uma tag anotada (objeto com autor, data e mensagem)
+        #[arg(short, long)]
+        annotate: bool,
+        /// Mensagem da tag anotada (implica -a)
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Remove a tag
+        #[arg(short = 'd', long = "delete")]
+        delete: bool,
+        /// Substitui uma tag existente
+        #[arg(short, long)]
+        force: bool,
+        /// Lista as tags
+        #[arg(short = 'l', long = "list")]
+        list: bool,
+    },
+    /// Troca o HEAD para outro branch
+    Switch {
+        /// Branch de destino
+        name: Option<String>,
+        /// Cria o branch a partir do HEAD antes de trocar
+        #[arg(short = 'c', long = "create")]
+        create: Option<String>,
+        /// Destaca o HEAD em um commit específico
+        #[arg(long)]
+        detach: Option<String>,
+        /// Descarta mudanças locais que seriam sobrescritas
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Integra outro branch ou commit ao HEAD atual (merge de três vias)
+    Merge {
+        /// Branch ou commit a integrar
+        #[arg(required_unless_present = "abort")]
+        rev: Option<String>,
+        /// Mensagem do commit de merge
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Cancela o merge em andamento
+        #[arg(long, conflicts_with = "rev")]
+        abort: bool,
+    },
+    /// Aplica sobre o HEAD as mudanças introduzidas por um commit
+    CherryPick {
+        /// Commit a aplicar
+        #[arg(required_unless_present_any = ["continue_", "abort"])]
+        rev: Option<String>,
+        /// Conclui o cherry-pick parado depois de resolver os conflitos
+        #[arg(long = "continue", conflicts_with_all = ["rev", "abort"])]
+        continue_: bool,
+        /// Cancela o cherry-pick parado
+        #[arg(long, conflicts_with = "rev")]
+        abort: bool,
+    },
+    /// Cria um commit que desfaz as mudanças introduzidas por um commit
+    Revert {
+        /// Commit a desfazer
+        #[arg(required_unless_present_any = ["continue_", "abort"])]
+        rev: Option<String>,
+        /// Conclui o revert parado depois de resolver os conflitos
+        #[arg(long = "continue", conflicts_with_all = ["rev", "abort"])]
+        continue_: bool,
+        /// Cancela o revert parado
+        #[arg(long, conflicts_with = "rev")]
+        abort: bool,
+    },
+    /// Atualiza o working tree para um branch ou commit
+    Checkout {
+        /// Branch ou commit de destino
+        rev: String,
+        /// Descarta mudanças locais que seriam sobrescritas
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Restaura arquivos do working tree a partir do index ou de um commit
+    Restore {
+        /// Arquivos ou diretórios a restaurar
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Commit de origem (padrão: index)
+        #[arg(short, long)]
+        source: Option<String>,
+        /// Descarta mudanças locais que seriam sobrescritas
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Move o branch atual para outro commit, ou tira arquivos do staging area
+    Reset {
+        /// Commit de destino (padrão: HEAD); sem "--", um caminho que não seja revisão é tirado do staging
+        target: Option<String>,
+        /// Mantém o index e o working tree (as mudanças ficam staged)
+        #[arg(long, group = "mode")]
+        soft: bool,
+        /// Esvazia o staging area, mantendo o working tree (padrão)
+        #[arg(long, group = "mode")]
+        mixed: bool,
+        /// Descarta as mudanças rastreadas do index e do working tree
+        #[arg(long, group = "mode")]
+        hard: bool,
+        /// Caminhos a voltar no index para a versão do commit (após "--")
+        #[arg(last = true)]
+        paths: Vec<String>,
+    },
+    /// Guarda mudanças locais em uma pilha e as reaplica depois
+    Stash {
+        #[command(subcommand)]
+        command: Option<StashCommands>,
+    },
+    /// Empacota os objetos em um único pack com compressão delta
+    Repack,
+    /// Remove objetos inalcançáveis e reempacota o repositório
+    Gc {
+        /// Remove objetos inalcançáveis mais antigos que isso (now, never, 30m, 12h, 14d, 2w)
+        #[arg(long, default_value = gc::DEFAULT_PRUNE_EXPIRY, value_parser = gc::parse_prune_expiry)]
+        prune: gc::PruneExpiry,
+    },
+    /// Verifica a integridade dos objetos e referências
+    ///
+    /// Código de saída: 1 = objetos pendentes (inalcançáveis), 2 = objetos
+    /// ausentes, 4 = objetos corrompidos, 8 = falha na verificação (bits combinados)
+    Fsck,
     /// Explica um commit usando IA (requer hash do commit)
     Explain {
-        /// Hash do commit para explicar
+        /// Commit para explicar (hash, prefixo, branch, HEAD~n...)
         commit_hash: String,
     },
     /// Lista todos os commits com embeddings IA disponíveis
@@ -66,12 +262,43 @@ enum Commands {
         /// Pergunta sobre o código ou commits
         #[arg(long = "question", short = 'q')]
         question: String,
-        /// Limitar busca a um commit específico (opcional)
+        /// Limitar busca a um commit específico (hash, prefixo, branch, HEAD~n...)
         #[arg(long)]
         commit: Option<String>,
     },
 }
 
+/// Subcomandos de `cogit stash` (sem subcomando, equivale a `push`)
+#[derive(Subcommand)]
+enum StashCommands {
+    /// Guarda as mudanças rastreadas (staged ou não) e limpa o working tree
+    Push {
+        /// Descrição do stash
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    /// Lista os stashes, do mais recente para o mais antigo
+    List,
+    /// Reaplica um stash, mantendo-o na pilha
+    Apply {
+        /// Stash a aplicar (stash@{n} ou n; padrão: o mais recente)
+        #[arg(default_value = "0")]
+        stash: String,
+    },
+    /// Reaplica um stash e o remove da pilha se não houver conflitos
+    Pop {
+        /// Stash a aplicar (stash@{n} ou n; padrão: o mais recente)
+        #[arg(default_value = "0")]
+        stash: String,
+    },
+    /// Remove um stash da pilha
+    Drop {
+        /// Stash a remover (stash@{n} ou n; padrão: o mais recente)
+        #[arg(default_value = "0")]
+        stash: String,
+    },
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
@@ -83,21 +310,73 @@ async fn main() {
                 Err(e) => eprintln!("Erro ao inicializar repositório: {}", e),
             }
         }
-        Commands::Add { files } => {
+        Commands::Config { key, value, global } => {
+            let repo = CogitRepository::open(".").ok();
+            let config_path = match (&repo, global) {
+                (_, true) => config::global_config_path(),
+                (Some(repo), false) => Some(repo.config_path()),
+                (None, false) => None,
+            };
+            let Some(config_path) = config_path else {
+                eprintln!("❌ Erro: {}", cogit::CogitError::NotARepository);
+                return;
+            };
+
+            match value {
+                Some(value) => match config::write_config_value(&config_path, &key, &value) {
+                    Ok(()) => println!("✅ {} = {}", key, value),
+                    Err(e) => eprintln!("❌ Erro ao gravar configuração: {}", e),
+                },
+                None => {
+                    // Sem --global, mostra o valor efetivo (repositório, depois usuário)
+                    let current = match (&repo, global) {
+                        (Some(repo), false) => repo.config_value(&key),
+                        _ => config::read_config_value(&config_path, &key),
+                    };
+                    match current {
+                        Ok(Some(value)) => println!("{}", value),
+                        Ok(None) => eprintln!("ℹ️  {} não está definida", key),
+                        Err(e) => eprintln!("❌ Erro ao ler configuração: {}", e),
+                    }
+                }
+            }
+        }
+        Commands::Add { files, force } => {
             match CogitRepository::open(".") {
-                Ok(_) => {
+                Ok(repo) => {
                     let cogit_dir = std::path::Path::new(".").join(".cogit");
                     let mut diff_engine = DiffEngine::new(cogit_dir);
                     
-                    if files == "." {
-                        // Adicionar todos os arquivos
+                    if !std::path::Path::new(&files).is_file() {
+                        // Adicionar todos os arquivos sob o caminho (ou o repositório inteiro com ".");
+                        // o walker do status já respeita as regras do .cogitignore
                         match diff_engine.get_status(std::path::Path::new(".")) {
                             Ok(status_list) => {
+                                let status_list: Vec<_> = status_list
+                                    .into_iter()
+                                    .filter(|file_status| diff::path_matches(&file_status.file_path, &files))
+                                    .collect();
+                                if status_list.is_empty() && !std::path::Path::new(&files).exists() {
+                                    eprintln!("❌ O caminho '{}' não corresponde a nenhum arquivo", files);
+                                    return;
+                                }
                                 let mut added_count = 0;
                                 for file_status in status_list {
                                     match file_status.status {
+                                        diff::WorkingTreeStatus::Conflicted
+                                            if !std::path::Path::new(&file_status.file_path).exists() => {
+                                            let file_path = std::path::Path::new(&file_status.file_path);
+                                            match diff_engine.stage_deletion(file_path) {
+                                                Ok(_) => {
+                                                    println!("Resolvido (removido): {}", file_status.file_path);
+                                                    added_count += 1;
+                                                }
+                                                Err(e) => eprintln!("Erro ao remover {}: {}", file_status.file_path, e),
+                                            }
+                                        }
                                         diff::WorkingTreeStatus::Untracked | 
-                                        diff::WorkingTreeStatus::Modified => {
+                                        diff::WorkingTreeStatus::Modified |
+                                        diff::WorkingTreeStatus::Conflicted => {
                                             let file_path = std::path::Path::new(&file_status.file_path);
                                             match diff_engine.add_to_staging(file_path) {
                                                 Ok(_) => {
@@ -107,6 +386,16 @@ async fn main() {
                                                 Err(e) => eprintln!("Erro ao adicionar {}: {}", file_status.file_path, e),
                                             }
                                         }
+                                        diff::WorkingTreeStatus::Deleted => {
+                                            let file_path = std::path::Path::new(&file_status.file_path);
+                                            match diff_engine.stage_deletion(file_path) {
+                                                Ok(_) => {
+                                                    println!("Removido: {}", file_status.file_path);
+                                                    added_count += 1;
+                                                }
+                                                Err(e) => eprintln!("Erro ao remover {}: {}", file_status.file_path, e),
+                                            }
+                                        }
                                         _ => {} // Arquivo já staged ou sem mudanças
                                     }
                                 }
@@ -121,37 +410,69 @@ async fn main() {
                     } else {
                         // Adicionar arquivo específico
                         let file_path = std::path::Path::new(&files);
-                        match diff_engine.add_to_staging(file_path) {
-                            Ok(_) => println!("✅ Arquivo {} adicionado ao staging area", files),
-                            Err(e) => eprintln!("Erro ao adicionar arquivo: {}", e),
+                        let ignored = !force && match repo.is_untracked_ignored(file_path) {
+                            Ok(ignored) => ignored,
+                            Err(e) => {
+                                eprintln!("Erro ao verificar regras de ignore: {}", e);
+                                return;
+                            }
+                        };
+                        if ignored {
+                            eprintln!("❌ O caminho {} é ignorado por um arquivo .cogitignore", files);
+                            eprintln!("   Use 'cogit add -f {}' para adicioná-lo mesmo assim", files);
+                        } else {
+                            match diff_engine.add_to_staging(file_path) {
+                                Ok(_) => println!("✅ Arquivo {} adicionado ao staging area", files),
+                                Err(e) => eprintln!("Erro ao adicionar arquivo: {}", e),
+                            }
                         }
                     }
                 }
                 Err(e) => eprintln!("Erro: {}", e),
             }
         }
-        Commands::Diff { file, staged: _ } => {
+        Commands::Rm { paths, cached, force } => {
             match CogitRepository::open(".") {
-                Ok(_) => {
+                Ok(repo) => {
+                    match repo.remove_paths(&paths, cached, force) {
+                        Ok(removed) => {
+                            for path in &removed {
+                                println!("rm '{}'", path);
+                            }
+                            println!("✅ {} remoção(ões) registrada(s) no staging area", removed.len());
+                        }
+                        Err(e) => eprintln!("❌ Erro ao remover: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Diff { revs, file, staged, paths } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
                     let cogit_dir = std::path::Path::new(".").join(".cogit");
                     let diff_engine = DiffEngine::new(cogit_dir);
+                    let root = std::path::Path::new(".");
+                    let paths: Vec<String> = paths.into_iter().chain(file).collect();
                     
-                    match file {
-                        Some(file_path) => {
-                            // Mostrar diff de arquivo específico
-                            let path = std::path::Path::new(&file_path);
-                            match diff_engine.show_file_diff(path) {
-                                Ok(_) => {}
-                                Err(e) => eprintln!("Erro ao mostrar diff: {}", e),
-                            }
-                        }
-                        None => {
-                            // Mostrar diff de todos os arquivos
-                            match diff_engine.show_all_diffs(std::path::Path::new(".")) {
-                                Ok(_) => {}
-                                Err(e) => eprintln!("Erro ao mostrar diffs: {}", e),
-                            }
+                    let commits: Result<Vec<String>, _> = revs.iter()
+                        .map(|rev| repo.resolve_revision(rev))
+                        .collect();
+                    
+                    let result = match commits.as_deref() {
+                        Err(e) => {
+                            eprintln!("❌ Revisão inválida: {}", e);
+                            return;
                         }
+                        Ok([]) => diff_engine.show_diffs(root, staged, &paths),
+                        Ok([commit]) => diff_engine.diff_commit_working_tree(root, commit, &paths)
+                            .map(|diffs| diff_engine.print_diffs(&diffs)),
+                        Ok([old, new, ..]) => diff_engine.diff_commits(old, new, &paths)
+                            .map(|diffs| diff_engine.print_diffs(&diffs)),
+                    };
+                    
+                    if let Err(e) = result {
+                        eprintln!("Erro ao mostrar diffs: {}", e);
                     }
                 }
                 Err(e) => eprintln!("Erro: {}", e),
@@ -164,9 +485,35 @@ async fn main() {
                     let cogit_dir = std::path::Path::new(".").join(".cogit");
                     let diff_engine = DiffEngine::new(cogit_dir.clone());
                     
+                    // Ao concluir um merge, a mensagem sugerida é usada se -m for omitido
+                    let merging = repo.merge_in_progress();
+                    let message = match message {
+                        Some(message) => message,
+                        None => match repo.merge_message() {
+                            Ok(Some(message)) if merging => message,
+                            Ok(_) => {
+                                eprintln!("❌ Informe a mensagem do commit com -m");
+                                return;
+                            }
+                            Err(e) => {
+                                eprintln!("❌ Erro ao ler a mensagem do merge: {}", e);
+                                return;
+                            }
+                        },
+                    };
+                    
+                    // O lock do index vale da leitura até a limpeza: um add no meio não se perde
+                    let lock = match diff_engine.lock_staging_area() {
+                        Ok(lock) => lock,
+                        Err(e) => {
+                            eprintln!("❌ Erro ao acessar staging area: {}", e);
+                            return;
+                        }
+                    };
+                    
                     match diff_engine.load_staging_area() {
                         Ok(staging_area) => {
-                            if staging_area.entries.is_empty() {
+                            if staging_area.entries.is_empty() && !merging {
                                 eprintln!("❌ Nenhuma mudança no staging area.");
                                 eprintln!("   Use 'cogit add .' para adicionar arquivos antes do commit.");
                                 return;
@@ -175,10 +522,15 @@ async fn main() {
                             // Processar apenas arquivos staged
                             println!("📦 Criando commit com {} arquivo(s) staged...", staging_area.entries.len());
                             
-                            match repo.commit(&message) {
+                            match repo.commit(&message, &staging_area) {
                                 Ok(hash) => {
                                     println!("✅ Commit criado: {}", hash);
                                     
+                                    // Limpar staging area (liberando o lock) antes da análise IA, que pode demorar
+                                    if let Err(e) = diff_engine.clear_staging_area(lock) {
+                                        eprintln!("⚠️  Aviso: Erro ao limpar staging area: {}", e);
+                                    }
+                                    
                                     // Processar embeddings IA otimizado (apenas patches)
                                     if !skip_ai {
                                         println!("🧠 Iniciando análise semântica otimizada...");
@@ -208,15 +560,6 @@ async fn main() {
                                             println!("   Ou use --skip-ai para pular a análise");
                                         }
                                     }
-                                    
-                                    // Limpar staging area após commit bem-sucedido
-                                    let empty_staging = diff::StagingArea {
-                                        entries: std::collections::HashMap::new(),
-                                        last_updated: chrono::Utc::now(),
-                                    };
-                                    if let Err(e) = diff_engine.save_staging_area(&empty_staging) {
-                                        eprintln!("⚠️  Aviso: Erro ao limpar staging area: {}", e);
-                                    }
                                 }
                                 Err(e) => eprintln!("❌ Erro ao criar commit: {}", e),
                             }
@@ -237,7 +580,22 @@ async fn main() {
                             } else {
                                 for commit in commits {
                                     println!("{} - {}", commit.hash, commit.message);
-                                    println!("   {}", commit.timestamp);
+                                    if commit.parents.len() > 1 {
+                                        let parents: Vec<&str> = commit.parents.iter().map(|parent| &parent[..7]).collect();
+                                        println!("   Merge: {}", parents.join(" "));
+                                    }
+                                    match &commit.author {
+                                        Some(author) => {
+                                            println!("   Autor: {}", author);
+                                            if let Some(committer) = commit.committer.as_ref().filter(|committer| {
+                                                committer.name != author.name || committer.email != author.email
+                                            }) {
+                                                println!("   Committer: {}", committer);
+                                            }
+                                            println!("   {}", author.timestamp);
+                                        }
+                                        None => println!("   {}", commit.timestamp),
+                                    }
                                     println!();
                                 }
                             }
@@ -248,6 +606,27 @@ async fn main() {
                 Err(e) => eprintln!("❌ Erro: {}", e),
             }
         }
+        Commands::Reflog { reference } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    let entries = repo.reflog_name(&reference).and_then(|ref_name| match ref_name {
+                        Some(ref_name) => repo.read_reflog(&ref_name),
+                        None => Err(cogit::CogitError::UnknownRevision(reference.clone())),
+                    });
+
+                    match entries {
+                        Ok(entries) if entries.is_empty() => println!("Nenhum movimento registrado para {}", reference),
+                        Ok(entries) => {
+                            for (position, entry) in entries.iter().enumerate() {
+                                println!("{} {}@{{{}}}: {}", &entry.new[..7], reference, position, entry.message);
+                            }
+                        }
+                        Err(e) => eprintln!("❌ Erro ao ler reflog: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
         Commands::Status => {
             match CogitRepository::open(".") {
                 Ok(repo) => {
@@ -262,30 +641,61 @@ async fn main() {
                             match diff_engine.get_status(std::path::Path::new(".")) {
                                 Ok(file_statuses) => {
                                     let mut staged_files = Vec::new();
+                                    let mut conflicted_files = Vec::new();
                                     let mut modified_files = Vec::new();
                                     let mut untracked_files = Vec::new();
                                     
                                     for file_status in file_statuses {
                                         match file_status.status {
-                                            diff::WorkingTreeStatus::Staged => staged_files.push(file_status.file_path),
-                                            diff::WorkingTreeStatus::Modified => modified_files.push(file_status.file_path),
+                                            diff::WorkingTreeStatus::Staged => {
+                                                let label = match (&file_status.head_hash, &file_status.index_hash) {
+                                                    (_, None) => "removido",
+                                                    (None, Some(_)) => "adicionado",
+                                                    (Some(_), Some(_)) => "modificado",
+                                                };
+                                                staged_files.push((label, file_status.file_path));
+                                            }
+                                            diff::WorkingTreeStatus::Modified => modified_files.push(("modificado", file_status.file_path)),
+                                            diff::WorkingTreeStatus::Deleted => modified_files.push(("removido", file_status.file_path)),
                                             diff::WorkingTreeStatus::Untracked => untracked_files.push(file_status.file_path),
+                                            diff::WorkingTreeStatus::Conflicted => conflicted_files.push(file_status.file_path),
                                             diff::WorkingTreeStatus::Unchanged => {} // Não mostrar arquivos sem mudanças
-                                            diff::WorkingTreeStatus::Deleted => {} // TODO: implementar quando necessário
+                                        }
+                                    }
+                                    
+                                    if repo.merge_in_progress() {
+                                        if conflicted_files.is_empty() {
+                                            println!("\n🔀 Merge em andamento: conflitos resolvidos, use 'cogit commit' para concluir");
+                                        } else {
+                                            println!("\n🔀 Merge em andamento: resolva os conflitos e use 'cogit add' (ou 'cogit merge --abort')");
+                                        }
+                                    }
+                                    if let Some(kind) = repo.pick_in_progress() {
+                                        if conflicted_files.is_empty() {
+                                            println!("\n🍒 {} em andamento: conflitos resolvidos, use 'cogit {} --continue' para concluir", kind.name(), kind.name());
+                                        } else {
+                                            println!("\n🍒 {} em andamento: resolva os conflitos e use 'cogit add' (ou 'cogit {} --abort')", kind.name(), kind.name());
+                                        }
+                                    }
+                                    
+                                    if !conflicted_files.is_empty() {
+                                        println!("\n⚔️  Conflitos não resolvidos:");
+                                        for file in &conflicted_files {
+                                            println!("  conflito: {}", file);
                                         }
                                     }
                                     
                                     if !staged_files.is_empty() {
                                         println!("\n🟢 Mudanças no staging area:");
-                                        for file in &staged_files {
-                                            println!("  adicionado: {}", file);
+                                        for (label, file) in &staged_files {
+                                            println!("  {}: {}", label, file);
                                         }
                                     }
                                     
                                     if !modified_files.is_empty() {
                                         println!("\n🟡 Mudanças não staged:");
-                                        for file in &modified_files {
-                                            println!("  modificado: {}", file);
+                                        for (label, file) in &modified_files {
+                                            println!("  {}: {}", label, file);
                                         }
                                     }
                                     
@@ -296,7 +706,8 @@ async fn main() {
                                         }
                                     }
                                     
-                                    if staged_files.is_empty() && modified_files.is_empty() && untracked_files.is_empty() {
+                                    if staged_files.is_empty() && conflicted_files.is_empty()
+                                        && modified_files.is_empty() && untracked_files.is_empty() {
                                         println!("\n✨ Working tree limpo - nenhuma mudança para commit");
                                     }
                                 }
@@ -305,13 +716,11 @@ async fn main() {
                             
                             // Mostrar informações de IA se disponível
                             if let Ok(engine) = EmbeddingEngine::new(cogit_dir) {
-                                match engine.list_embedded_commits() {
-                                    Ok(commits) => {
-                                        if !commits.is_empty() {
-                                            println!("\n🤖 Commits com análise IA: {}", commits.len());
-                                        }
+                                // Silenciar erros aqui
+                                if let Ok(commits) = engine.list_embedded_commits() {
+                                    if !commits.is_empty() {
+                                        println!("\n🤖 Commits com análise IA: {}", commits.len());
                                     }
-                                    Err(_) => {} // Silenciar erros aqui
                                 }
                             }
                         }
@@ -321,7 +730,483 @@ async fn main() {
                 Err(e) => eprintln!("❌ Erro: {}", e),
             }
         }
+        Commands::Branch { name, start_point, delete, force_delete, list } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    match name {
+                        Some(name) if delete || force_delete => {
+                            match repo.delete_branch(&name, force_delete) {
+                                Ok(_) => println!("🗑️  Branch {} removido", name),
+                                Err(e) => eprintln!("❌ Erro ao remover branch: {}", e),
+                            }
+                        }
+                        Some(name) if !list => {
+                            let start = start_point.as_deref().unwrap_or("HEAD");
+                            match repo.resolve_revision(start).and_then(|hash| repo.create_branch(&name, &hash)) {
+                                Ok(_) => println!("🌿 Branch {} criado", name),
+                                Err(e) => eprintln!("❌ Erro ao criar branch: {}", e),
+                            }
+                        }
+                        _ => {
+                            match repo.list_branches() {
+                                Ok(branches) => {
+                                    let current = repo.current_branch().ok().flatten();
+                                    if branches.is_empty() {
+                                        println!("Nenhum branch com commits ainda");
+                                    }
+                                    for branch in branches {
+                                        let marker = if current.as_deref() == Some(branch.as_str()) { "*" } else { " " };
+                                        println!("{} {}", marker, branch);
+                                    }
+                                }
+                                Err(e) => eprintln!("❌ Erro ao listar branches: {}", e),
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Tag { name, rev, annotate, message, delete, force, list } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    match name {
+                        Some(name) if delete => {
+                            match repo.delete_tag(&name) {
+                                Ok(_) => println!("🗑️  Tag {} removida", name),
+                                Err(e) => eprintln!("❌ Erro ao remover tag: {}", e),
+                            }
+                        }
+                        Some(name) if !list => {
+                            if annotate && message.is_none() {
+                                eprintln!("❌ Informe a mensagem da tag anotada com -m");
+                                return;
+                            }
+                            let target = rev.as_deref().unwrap_or("HEAD");
+                            match repo.resolve_revision(target).and_then(|hash| {
+                                repo.create_tag(&name, &hash, message.as_deref(), force).map(|_| hash)
+                            }) {
+                                Ok(hash) => println!("🏷️  Tag {} criada em {}", name, &hash[..7]),
+                                Err(e) => eprintln!("❌ Erro ao criar tag: {}", e),
+                            }
+                        }
+                        pattern => {
+                            match repo.list_tags(pattern.as_deref()) {
+                                Ok(tags) => {
+                                    for tag in tags {
+                                        match tag.annotation {
+                                            Some(annotation) => println!(
+                                                "🏷️  {} -> {} ({})",
+                                                tag.name,
+                                                &annotation.object[..7.min(annotation.object.len())],
+                                                annotation.message.lines().next().unwrap_or_default()
+                                            ),
+                                            None => println!("🏷️  {} -> {}", tag.name, &tag.target[..7.min(tag.target.len())]),
+                                        }
+                                    }
+                                }
+                                Err(e) => eprintln!("❌ Erro ao listar tags: {}", e),
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Switch { name, create, detach, force } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    let result = if let Some(rev) = detach {
+                        repo.resolve_revision(&rev)
+                            .and_then(|hash| repo.checkout_detached(&hash, force).map(|_| format!("HEAD destacado em {}", &hash[..7])))
+                    } else if let Some(new_branch) = create {
+                        let reason = format!("checkout: de {} para {}", repo.head_description().unwrap_or_default(), new_branch);
+                        repo.resolve_revision("HEAD")
+                            .and_then(|hash| repo.create_branch(&new_branch, &hash))
+                            .and_then(|_| repo.set_head_branch(&new_branch, &reason))
+                            .map(|_| format!("Trocado para novo branch '{}'", new_branch))
+                    } else if let Some(branch) = name {
+                        repo.switch_branch(&branch, force)
+                            .map(|_| format!("Trocado para o branch '{}'", branch))
+                    } else {
+                        eprintln!("❌ Informe o branch de destino (ou use -c/--detach)");
+                        return;
+                    };
+
+                    match result {
+                        Ok(message) => println!("🔀 {}", message),
+                        Err(e) => eprintln!("❌ Erro ao trocar de branch: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Merge { rev, message, abort } => {
+            match CogitRepository::open(".") {
+                Ok(mut repo) => {
+                    if abort {
+                        match repo.abort_merge() {
+                            Ok(()) => println!("↩️  Merge cancelado"),
+                            Err(e) => eprintln!("❌ Erro ao cancelar merge: {}", e),
+                        }
+                        return;
+                    }
+
+                    let rev = rev.unwrap_or_default();
+                    let theirs = match repo.resolve_revision(&rev) {
+                        Ok(hash) => hash,
+                        Err(e) => {
+                            eprintln!("❌ Revisão inválida: {}", e);
+                            return;
+                        }
+                    };
+                    let message = message.unwrap_or_else(|| match repo.read_branch(&rev) {
+                        Ok(Some(_)) => format!("Merge branch '{}'", rev),
+                        _ => format!("Merge commit '{}'", rev),
+                    });
+
+                    match repo.merge(&theirs, &rev, &message) {
+                        Ok(merge::MergeOutcome::UpToDate) => println!("✨ Já atualizado"),
+                        Ok(merge::MergeOutcome::FastForward(hash)) => println!("⏩ Fast-forward até {}", &hash[..7]),
+                        Ok(merge::MergeOutcome::Merged(hash)) => println!("🔀 Merge concluído: {}", hash),
+                        Ok(merge::MergeOutcome::Conflicts(paths)) => {
+                            for path in &paths {
+                                println!("⚔️  Conflito em {}", path);
+                            }
+                            println!("❌ Merge com conflitos: edite os arquivos, use 'cogit add' e depois 'cogit commit'");
+                            println!("   Ou use 'cogit merge --abort' para desistir");
+                        }
+                        Err(e) => eprintln!("❌ Erro no merge: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::CherryPick { ref rev, continue_, abort } | Commands::Revert { ref rev, continue_, abort } => {
+            let kind = match cli.command {
+                Commands::Revert { .. } => sequencer::PickKind::Revert,
+                _ => sequencer::PickKind::CherryPick,
+            };
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    if abort {
+                        match repo.abort_pick(kind) {
+                            Ok(()) => println!("↩️  {} cancelado", kind.name()),
+                            Err(e) => eprintln!("❌ Erro ao cancelar {}: {}", kind.name(), e),
+                        }
+                        return;
+                    }
+
+                    let result = if continue_ {
+                        repo.continue_pick(kind).map(sequencer::PickOutcome::Committed)
+                    } else {
+                        match repo.resolve_revision(rev.as_deref().unwrap_or_default()) {
+                            Ok(hash) => repo.pick(kind, &hash),
+                            Err(e) => {
+                                eprintln!("❌ Revisão inválida: {}", e);
+                                return;
+                            }
+                        }
+                    };
+
+                    match result {
+                        Ok(sequencer::PickOutcome::Committed(hash)) => println!("✅ Commit criado: {}", hash),
+                        Ok(sequencer::PickOutcome::Empty) => println!("ℹ️  As mudanças já estão no HEAD; nenhum commit criado"),
+                        Ok(sequencer::PickOutcome::Conflicts(paths)) => {
+                            for path in &paths {
+                                println!("⚔️  Conflito em {}", path);
+                            }
+                            println!(
+                                "❌ {} com conflitos: edite os arquivos, use 'cogit add' e depois 'cogit {} --continue'",
+                                kind.name(),
+                                kind.name()
+                            );
+                            println!("   Ou use 'cogit {} --abort' para desistir", kind.name());
+                        }
+                        Err(e) => eprintln!("❌ Erro no {}: {}", kind.name(), e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Checkout { rev, force } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    let result = match repo.read_branch(&rev) {
+                        Ok(Some(_)) => repo.switch_branch(&rev, force)
+                            .map(|_| format!("Trocado para o branch '{}'", rev)),
+                        // Nomes que não podem ser branches (HEAD~1, hashes com ^...) são revisões
+                        Ok(None) | Err(CogitError::InvalidRefName(_)) => repo.resolve_revision(&rev)
+                            .and_then(|hash| repo.checkout_detached(&hash, force).map(|_| format!("HEAD destacado em {}", &hash[..7]))),
+                        Err(e) => Err(e),
+                    };
+
+                    match result {
+                        Ok(message) => println!("🔀 {}", message),
+                        Err(e) => eprintln!("❌ Erro no checkout: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Restore { paths, source, force } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    let source_hash = match source.as_deref().map(|rev| repo.resolve_revision(rev)).transpose() {
+                        Ok(hash) => hash,
+                        Err(e) => {
+                            eprintln!("❌ Erro ao resolver origem: {}", e);
+                            return;
+                        }
+                    };
+
+                    match repo.restore_paths(&paths, source_hash.as_deref(), force) {
+                        Ok(restored) => {
+                            for path in &restored {
+                                println!("Restaurado: {}", path);
+                            }
+                            println!("✅ {} arquivo(s) restaurado(s)", restored.len());
+                        }
+                        Err(e) => eprintln!("❌ Erro ao restaurar: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Reset { target, soft, mixed, hard, paths } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    // Sem "--", um argumento que não é revisão mas existe como caminho é um caminho
+                    let (target, paths) = match target {
+                        Some(target)
+                            if paths.is_empty()
+                                && !(soft || mixed || hard)
+                                && repo.resolve_revision(&target).is_err()
+                                && (std::path::Path::new(&target).exists()
+                                    || repo.index_files().is_ok_and(|files| {
+                                        files.keys().any(|file| diff::path_matches(file, &target))
+                                    })) =>
+                        {
+                            (None, vec![target])
+                        }
+                        target => (target, paths),
+                    };
+
+                    let source = match target.as_deref().map(|rev| repo.resolve_revision(rev)).transpose() {
+                        Ok(hash) => hash,
+                        Err(e) => {
+                            eprintln!("❌ Revisão inválida: {}", e);
+                            return;
+                        }
+                    };
+
+                    if !paths.is_empty() {
+                        if soft || hard {
+                            eprintln!("❌ --soft e --hard não podem ser usados com caminhos");
+                            return;
+                        }
+                        match repo.reset_paths(source.as_deref(), &paths) {
+                            Ok(reset) => {
+                                for path in &reset {
+                                    println!("Fora do staging: {}", path);
+                                }
+                                println!("✅ {} arquivo(s) atualizado(s) no index", reset.len());
+                            }
+                            Err(e) => eprintln!("❌ Erro ao atualizar o index: {}", e),
+                        }
+                        return;
+                    }
+
+                    let mode = if soft {
+                        reset::ResetMode::Soft
+                    } else if hard {
+                        reset::ResetMode::Hard
+                    } else {
+                        reset::ResetMode::Mixed
+                    };
+                    let label = target.as_deref().unwrap_or("HEAD");
+                    let result = match source {
+                        Some(hash) => Ok(hash),
+                        None => repo.resolve_revision("HEAD"),
+                    }
+                    .and_then(|hash| repo.reset(&hash, mode, label).map(|_| hash));
+
+                    match result {
+                        Ok(hash) => println!("↩️  HEAD agora em {}", &hash[..7]),
+                        Err(e) => eprintln!("❌ Erro no reset: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Stash { command } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    // Informa o resultado de um apply; true se foi aplicado sem conflitos
+                    let report_apply = |result: Result<merge::TreeMerge, cogit::CogitError>| match result {
+                        Ok(result) if result.conflicts.is_empty() => {
+                            println!("📤 Stash aplicado: {} arquivo(s) atualizado(s)", result.updated.len());
+                            true
+                        }
+                        Ok(result) => {
+                            for path in &result.conflicts {
+                                println!("⚔️  Conflito em {}", path);
+                            }
+                            println!("❌ Stash aplicado com conflitos: resolva os arquivos e use 'cogit add'; o stash foi mantido");
+                            false
+                        }
+                        Err(e) => {
+                            eprintln!("❌ Erro ao aplicar stash: {}", e);
+                            false
+                        }
+                    };
+
+                    match command.unwrap_or(StashCommands::Push { message: None }) {
+                        StashCommands::Push { message } => match repo.stash_push(message.as_deref()) {
+                            Ok(Some(_)) => println!("📥 Mudanças locais guardadas em stash@{{0}}"),
+                            Ok(None) => println!("ℹ️  Nenhuma mudança local para guardar"),
+                            Err(e) => eprintln!("❌ Erro ao guardar mudanças: {}", e),
+                        },
+                        StashCommands::List => match repo.stash_list() {
+                            Ok(entries) => {
+                                for entry in entries {
+                                    println!("stash@{{{}}}: {}", entry.position, entry.message);
+                                }
+                            }
+                            Err(e) => eprintln!("❌ Erro ao listar stashes: {}", e),
+                        },
+                        StashCommands::Apply { stash } => match stash::parse_stash_position(&stash) {
+                            Ok(position) => {
+                                report_apply(repo.stash_apply(position));
+                            }
+                            Err(e) => eprintln!("❌ Erro ao aplicar stash: {}", e),
+                        },
+                        StashCommands::Pop { stash } => match stash::parse_stash_position(&stash) {
+                            Ok(position) => {
+                                if report_apply(repo.stash_apply(position)) {
+                                    match repo.stash_drop(position) {
+                                        Ok(entry) => println!("🗑️  stash@{{{}}} removido ({})", position, &entry.hash[..7]),
+                                        Err(e) => eprintln!("❌ Erro ao remover stash: {}", e),
+                                    }
+                                }
+                            }
+                            Err(e) => eprintln!("❌ Erro ao aplicar stash: {}", e),
+                        },
+                        StashCommands::Drop { stash } => {
+                            match stash::parse_stash_position(&stash).and_then(|position| repo.stash_drop(position).map(|entry| (position, entry))) {
+                                Ok((position, entry)) => println!("🗑️  stash@{{{}}} removido ({})", position, &entry.hash[..7]),
+                                Err(e) => eprintln!("❌ Erro ao remover stash: {}", e),
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Repack => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    match repo.repack(None) {
+                        Ok(stats) => match stats.pack_name {
+                            Some(pack_name) => {
+                                println!(
+                                    "📦 {} objeto(s) empacotado(s) em {} ({} como delta)",
+                                    stats.objects, pack_name, stats.deltas
+                                );
+                                println!("   Tamanho: {} -> {} bytes", stats.size_before, stats.size_after);
+                            }
+                            None if stats.objects > 0 => {
+                                println!("ℹ️  Um pack não ocuparia menos espaço que os {} objeto(s) soltos; nada foi empacotado", stats.objects)
+                            }
+                            None => println!("ℹ️  Nenhum objeto para empacotar"),
+                        },
+                        Err(e) => eprintln!("❌ Erro ao empacotar objetos: {}", e),
+                    }
+                }
+                Err(e) => eprintln!("❌ Erro: {}", e),
+            }
+        }
+        Commands::Gc { prune } => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    match repo.gc(prune) {
+                        Ok(stats) => {
+                            println!("🧹 {} objeto(s) inalcançável(is) removido(s)", stats.pruned_objects);
+                            if stats.kept_recent > 0 {
+                                println!("   {} objeto(s) inalcançável(is) recente(s) mantido(s)", stats.kept_recent);
+                            }
+                            if stats.pruned_indexes > 0 {
+                                println!("🧠 {} índice(s) de embeddings removido(s)", stats.pruned_indexes);
+                            }
+                            if let Some(pack_name) = stats.repack.pack_name {
+                                println!(
+                                    "📦 {} objeto(s) empacotado(s) em {} ({} como delta)",
+                                    stats.repack.objects, pack_name, stats.repack.deltas
+                                );
+                            }
+                            println!("   Tamanho: {} -> {} bytes", stats.repack.size_before, stats.repack.size_after);
+                        }
+                        Err(e) => {
+                            eprintln!("❌ Erro na coleta de lixo: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("❌ Erro: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Fsck => {
+            match CogitRepository::open(".") {
+                Ok(repo) => {
+                    match repo.fsck() {
+                        Ok(report) => {
+                            for (name, reason) in &report.corrupt {
+                                println!("❌ corrompido {}: {}", name, reason);
+                            }
+                            for (hash, object_type, referrer) in &report.missing {
+                                println!("❓ ausente {} {} (referenciado por {})", object_type, hash, referrer);
+                            }
+                            for (hash, object_type) in &report.dangling {
+                                println!("🔸 pendente {} {}", object_type, hash);
+                            }
+
+                            let code = report.exit_code();
+                            if code == 0 {
+                                println!("✅ {} objeto(s) verificado(s), nenhum problema encontrado", report.checked);
+                            } else {
+                                println!(
+                                    "⚠️  {} objeto(s) verificado(s): {} corrompido(s), {} ausente(s), {} pendente(s)",
+                                    report.checked,
+                                    report.corrupt.len(),
+                                    report.missing.len(),
+                                    report.dangling.len()
+                                );
+                                std::process::exit(code);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("❌ Erro ao verificar repositório: {}", e);
+                            std::process::exit(fsck::FSCK_FAILED);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("❌ Erro: {}", e);
+                    std::process::exit(fsck::FSCK_FAILED);
+                }
+            }
+        }
         Commands::Explain { commit_hash } => {
+            let commit_hash = match CogitRepository::open(".").and_then(|repo| repo.resolve_revision(&commit_hash)) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    eprintln!("❌ Erro: {}", e);
+                    return;
+                }
+            };
             let cogit_dir = std::path::Path::new(".").join(".cogit");
             match EmbeddingEngine::new(cogit_dir) {
                 Ok(engine) => {
@@ -385,6 +1270,13 @@ async fn main() {
             }
         }
         Commands::Ask { question, commit } => {
+            let commit = match commit.map(|rev| CogitRepository::open(".").and_then(|repo| repo.resolve_revision(&rev))).transpose() {
+                Ok(hash) => hash,
+                Err(e) => {
+                    eprintln!("❌ Erro: {}", e);
+                    return;
+                }
+            };
             let cogit_dir = std::path::Path::new(".").join(".cogit");
             match EmbeddingEngine::new(cogit_dir) {
                 Ok(mut engine) => {
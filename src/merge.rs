@@ -0,0 +1,581 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::{is_binary, myers_diff, DiffEngine, EditOp, StagingEntry};
+use crate::lockfile::write_atomic;
+use crate::object::ObjectType;
+
+/// Marcadores de conflito (mesmo formato do git)
+const MARKER_OURS: &str = "<<<<<<<";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_THEIRS: &str = ">>>>>>>";
+
+/// Commit sendo integrado por um merge em andamento (.cogit/MERGE_HEAD)
+const MERGE_HEAD_FILE: &str = "MERGE_HEAD";
+
+/// Mensagem sugerida para o commit de merge (.cogit/MERGE_MSG)
+const MERGE_MSG_FILE: &str = "MERGE_MSG";
+
+/// Resultado de `cogit merge`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    UpToDate,                // O commit já faz parte do histórico do HEAD
+    FastForward(String),     // HEAD apenas avançou até o commit
+    Merged(String),          // Commit de merge criado
+    Conflicts(Vec<String>),  // Merge parado: arquivos com conflito
+}
+
+/// Resultado de um merge de três vias aplicado ao working tree e ao index
+#[derive(Debug, Clone, Default)]
+pub struct TreeMerge {
+    pub updated: Vec<String>,    // Caminhos alterados sem conflito
+    pub conflicts: Vec<String>,  // Caminhos com conflito, deixados para o usuário resolver
+}
+
+/// Resultado do merge de três vias de um arquivo texto
+#[derive(Debug, Clone)]
+pub struct TextMerge {
+    pub content: Vec<u8>,
+    pub conflicts: usize,  // Quantidade de blocos com marcadores de conflito
+}
+
+/// Bloco alterado em relação à base: as linhas [base_start, base_end) da base
+/// viraram as linhas [start, end) da outra versão
+#[derive(Debug, Clone, Copy)]
+struct Change {
+    base_start: usize,
+    base_end: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Como um caminho fica depois do merge
+enum Resolution {
+    Clean(Option<String>),                          // Novo blob (ou remoção), sem conflito
+    Conflict { content: Vec<u8>, hash: String },    // Conteúdo deixado no disco e hash registrado no index
+}
+
+/// Merge de três vias linha a linha
+///
+/// Os blocos alterados de cada lado são calculados com o diff de Myers contra
+/// a base. Mudanças de um só lado são aplicadas; mudanças dos dois lados que se
+/// sobrepõem (ou se tocam) na base viram um conflito, a menos que sejam idênticas.
+pub fn merge_text(base: &[u8], ours: &[u8], theirs: &[u8], ours_label: &str, theirs_label: &str) -> TextMerge {
+    let base_lines = split_lines(base);
+    let ours_lines = split_lines(ours);
+    let theirs_lines = split_lines(theirs);
+    let ours_changes = changes(&myers_diff(&base_lines, &ours_lines));
+    let theirs_changes = changes(&myers_diff(&base_lines, &theirs_lines));
+
+    let mut content = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut conflicts = 0;
+    let mut base_pos = 0;
+    let (mut next_ours, mut next_theirs) = (0, 0);
+
+    loop {
+        let start = match (ours_changes.get(next_ours), theirs_changes.get(next_theirs)) {
+            (None, None) => break,
+            (Some(ours), Some(theirs)) => ours.base_start.min(theirs.base_start),
+            (Some(change), None) | (None, Some(change)) => change.base_start,
+        };
+        push_lines(&mut content, &base_lines[base_pos..start]);
+
+        // Agrupa as mudanças dos dois lados que se sobrepõem ou se tocam na base
+        let (first_ours, first_theirs) = (next_ours, next_theirs);
+        let mut end = start;
+        loop {
+            if let Some(change) = ours_changes.get(next_ours).filter(|change| change.base_start <= end) {
+                end = end.max(change.base_end);
+                next_ours += 1;
+            } else if let Some(change) = theirs_changes.get(next_theirs).filter(|change| change.base_start <= end) {
+                end = end.max(change.base_end);
+                next_theirs += 1;
+            } else {
+                break;
+            }
+        }
+
+        let ours_side = side_lines(&ours_lines, &ours_changes[first_ours..next_ours], start, end);
+        let theirs_side = side_lines(&theirs_lines, &theirs_changes[first_theirs..next_theirs], start, end);
+
+        match (ours_side, theirs_side) {
+            (Some(ours_side), None) => push_lines(&mut content, ours_side),
+            (None, Some(theirs_side)) => push_lines(&mut content, theirs_side),
+            (Some(ours_side), Some(theirs_side)) if ours_side == theirs_side => push_lines(&mut content, ours_side),
+            (ours_side, theirs_side) => {
+                conflicts += 1;
+                content.extend_from_slice(format!("{} {}\n", MARKER_OURS, ours_label).as_bytes());
+                push_terminated_lines(&mut content, ours_side.unwrap_or_default());
+                content.extend_from_slice(format!("{}\n", MARKER_SEPARATOR).as_bytes());
+                push_terminated_lines(&mut content, theirs_side.unwrap_or_default());
+                content.extend_from_slice(format!("{} {}\n", MARKER_THEIRS, theirs_label).as_bytes());
+            }
+        }
+        base_pos = end;
+    }
+
+    push_lines(&mut content, &base_lines[base_pos..]);
+    TextMerge { content, conflicts }
+}
+
+/// Divide o conteúdo em linhas, mantendo o '\n' de cada uma
+fn split_lines(content: &[u8]) -> Vec<&[u8]> {
+    content.split_inclusive(|&byte| byte == b'\n').collect()
+}
+
+/// Blocos alterados de um script de edição, em ordem
+fn changes(ops: &[EditOp]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let mut current: Option<Change> = None;
+    let (mut base_pos, mut pos) = (0, 0);
+
+    for op in ops {
+        match *op {
+            EditOp::Equal(old, new) => {
+                changes.extend(current.take());
+                base_pos = old + 1;
+                pos = new + 1;
+            }
+            EditOp::Delete(old) => {
+                let change = current.get_or_insert(Change { base_start: base_pos, base_end: base_pos, start: pos, end: pos });
+                change.base_end = old + 1;
+            }
+            EditOp::Insert(new) => {
+                let change = current.get_or_insert(Change { base_start: base_pos, base_end: base_pos, start: pos, end: pos });
+                change.end = new + 1;
+            }
+        }
+    }
+
+    changes.extend(current);
+    changes
+}
+
+/// Linhas de um lado que correspondem ao trecho [start, end) da base
+/// (None se esse lado não mudou nada no trecho)
+fn side_lines<'a>(lines: &'a [&'a [u8]], changes: &[Change], start: usize, end: usize) -> Option<&'a [&'a [u8]]> {
+    let (first, last) = (changes.first()?, changes.last()?);
+    // Fora dos blocos alterados as linhas são iguais às da base
+    let from = first.start - (first.base_start - start);
+    let to = last.end + (end - last.base_end);
+    Some(&lines[from..to])
+}
+
+fn push_lines(content: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        content.extend_from_slice(line);
+    }
+}
+
+/// Como `push_lines`, mas garante o '\n' final (necessário antes de um marcador)
+fn push_terminated_lines(content: &mut Vec<u8>, lines: &[&[u8]]) {
+    push_lines(content, lines);
+    if lines.last().is_some_and(|line| !line.ends_with(b"\n")) {
+        content.push(b'\n');
+    }
+}
+
+impl CogitRepository {
+    /// Bases de merge entre dois commits: ancestrais comuns que não são
+    /// ancestrais de outro ancestral comum (mais de uma em históricos cruzados)
+    pub fn merge_bases(&self, first: &str, second: &str) -> Result<Vec<String>, CogitError> {
+        let mut first_ancestors = HashSet::new();
+        let mut pending = vec![first.to_string()];
+        while let Some(hash) = pending.pop() {
+            if first_ancestors.insert(hash.clone()) {
+                pending.extend(self.load_commit(&hash)?.parents);
+            }
+        }
+
+        // A partir do segundo commit, para em cada ancestral comum encontrado
+        let mut candidates = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![second.to_string()];
+        while let Some(hash) = pending.pop() {
+            if !visited.insert(hash.clone()) {
+                continue;
+            }
+            if first_ancestors.contains(&hash) {
+                candidates.push(hash);
+            } else {
+                pending.extend(self.load_commit(&hash)?.parents);
+            }
+        }
+
+        let mut bases = Vec::new();
+        for candidate in &candidates {
+            let mut redundant = false;
+            for other in candidates.iter().filter(|other| *other != candidate) {
+                if self.is_ancestor(candidate, other)? {
+                    redundant = true;
+                    break;
+                }
+            }
+            if !redundant {
+                bases.push(candidate.clone());
+            }
+        }
+        bases.sort();
+        Ok(bases)
+    }
+
+    /// Arquivos da base de um merge de três vias entre dois commits
+    ///
+    /// Com uma única base comum, são os arquivos dela. Em históricos cruzados,
+    /// as bases são combinadas entre si (cada uma com a anterior, usando
+    /// recursivamente a base comum das duas) em uma base virtual; conflitos
+    /// nessa combinação ficam com os marcadores no conteúdo, como no git.
+    /// Sem ancestral comum, a base é vazia.
+    pub fn merge_base_files(&self, first: &str, second: &str) -> Result<BTreeMap<String, String>, CogitError> {
+        let bases = self.merge_bases(first, second)?;
+        let Some((first_base, other_bases)) = bases.split_first() else {
+            return Ok(BTreeMap::new());
+        };
+
+        let mut files = self.commit_files(first_base)?;
+        let mut previous = first_base;
+        for base in other_bases {
+            let ancestor = self.merge_base_files(previous, base)?;
+            for (path, resolution) in self.resolve_paths(&ancestor, &files, &self.commit_files(base)?, &base[..7])? {
+                match resolution {
+                    Resolution::Clean(Some(hash)) => files.insert(path, hash),
+                    Resolution::Clean(None) => files.remove(&path),
+                    Resolution::Conflict { content, .. } => files.insert(path, self.store_object(ObjectType::Blob, &content)?),
+                };
+            }
+            previous = base;
+        }
+        Ok(files)
+    }
+
+    /// Integra um commit ao HEAD atual
+    ///
+    /// Se o HEAD já contém o commit nada é feito; se o commit descende do HEAD,
+    /// o branch apenas avança (fast-forward). Caso contrário é feito um merge de
+    /// três vias a partir da base comum: sem conflitos, o commit de merge (com
+    /// dois pais) é criado na hora; com conflitos, os arquivos recebem
+    /// marcadores e o merge fica em andamento até o próximo `cogit commit`.
+    pub fn merge(&mut self, theirs: &str, label: &str, message: &str) -> Result<MergeOutcome, CogitError> {
+        if self.merge_in_progress() {
+            return Err(CogitError::OperationInProgress("merge".to_string()));
+        }
+        if let Some(kind) = self.pick_in_progress() {
+            return Err(CogitError::OperationInProgress(kind.name().to_string()));
+        }
+
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let staged: Vec<String> = diff_engine.load_staging_area()?.entries.into_keys().collect();
+        if !staged.is_empty() {
+            return Err(CogitError::UncommittedChanges(staged));
+        }
+
+        let Some(ours) = self.head_commit()? else {
+            // Branch ainda sem commits: apenas adota o histórico do outro lado
+            self.checkout_tree(&self.commit_files(theirs)?, false)?;
+            self.update_head_commit(None, theirs, &format!("merge {}: fast-forward", label))?;
+            return Ok(MergeOutcome::FastForward(theirs.to_string()));
+        };
+
+        if self.is_ancestor(theirs, &ours)? {
+            return Ok(MergeOutcome::UpToDate);
+        }
+        if self.is_ancestor(&ours, theirs)? {
+            self.checkout_tree(&self.commit_files(theirs)?, false)?;
+            self.update_head_commit(Some(&ours), theirs, &format!("merge {}: fast-forward", label))?;
+            return Ok(MergeOutcome::FastForward(theirs.to_string()));
+        }
+
+        let base_files = self.merge_base_files(&ours, theirs)?;
+        let result = self.merge_trees(&base_files, &self.commit_files(&ours)?, &self.commit_files(theirs)?, label)?;
+
+        write_atomic(&self.cogit_dir().join(MERGE_HEAD_FILE), format!("{}\n", theirs).as_bytes())?;
+        write_atomic(&self.cogit_dir().join(MERGE_MSG_FILE), format!("{}\n", message).as_bytes())?;
+
+        if !result.conflicts.is_empty() {
+            return Ok(MergeOutcome::Conflicts(result.conflicts));
+        }
+
+        let lock = diff_engine.lock_staging_area()?;
+        let hash = self.commit(message, &diff_engine.load_staging_area()?)?;
+        diff_engine.clear_staging_area(lock)?;
+
+        Ok(MergeOutcome::Merged(hash))
+    }
+
+    /// Cancela o merge em andamento, devolvendo ao estado do HEAD os caminhos
+    /// que o merge alterou e limpando o index
+    pub fn abort_merge(&self) -> Result<(), CogitError> {
+        if !self.merge_in_progress() {
+            return Err(CogitError::NoOperationInProgress("merge".to_string()));
+        }
+
+        self.discard_staged_changes()?;
+        self.clear_merge_state()
+    }
+
+    /// Devolve ao estado do HEAD os caminhos presentes no staging area, no
+    /// working tree e no index (o que um merge interrompido alterou)
+    pub fn discard_staged_changes(&self) -> Result<(), CogitError> {
+        let head_files = self.head_files()?;
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        for path in diff_engine.load_staging_area()?.entries.keys() {
+            match head_files.get(path) {
+                Some(hash) => self.write_working_file(path, hash)?,
+                None => self.remove_working_file(path)?,
+            }
+        }
+
+        diff_engine.clear_staging_area(lock)
+    }
+
+    /// Aplica ao working tree e ao index um merge de três vias entre conjuntos de arquivos
+    ///
+    /// `ours` deve corresponder ao estado atual do working tree: se algum caminho
+    /// que o merge precisa alterar tiver mudanças locais (ou um arquivo não
+    /// rastreado no lugar), nada é alterado e a operação é recusada. Caminhos
+    /// em conflito ficam marcados no index até serem adicionados de novo.
+    pub fn merge_trees(
+        &self,
+        base: &BTreeMap<String, String>,
+        ours: &BTreeMap<String, String>,
+        theirs: &BTreeMap<String, String>,
+        theirs_label: &str,
+    ) -> Result<TreeMerge, CogitError> {
+        // Decide o resultado de cada caminho antes de tocar no disco
+        let resolutions = self.resolve_paths(base, ours, theirs, theirs_label)?;
+
+        let dirty: Vec<String> = resolutions
+            .iter()
+            .filter(|(path, _)| self.working_hash(path).as_ref() != ours.get(path))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !dirty.is_empty() {
+            return Err(CogitError::UncommittedChanges(dirty));
+        }
+
+        let head_files = self.head_files()?;
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        let mut staging_area = diff_engine.load_staging_area()?;
+        let mut result = TreeMerge::default();
+
+        for (path, resolution) in resolutions {
+            match resolution {
+                Resolution::Clean(Some(hash)) => {
+                    self.write_working_file(&path, &hash)?;
+                    let file_size = fs::metadata(self.root_path().join(&path))?.len();
+                    staging_area.entries.insert(path.clone(), StagingEntry::blob(&path, &hash, file_size));
+                    result.updated.push(path);
+                }
+                Resolution::Clean(None) => {
+                    self.remove_working_file(&path)?;
+                    if head_files.contains_key(&path) {
+                        staging_area.entries.insert(path.clone(), StagingEntry::deletion(&path));
+                    } else {
+                        staging_area.entries.remove(&path);
+                    }
+                    result.updated.push(path);
+                }
+                Resolution::Conflict { content, hash } => {
+                    self.write_working_content(&path, &content)?;
+                    let entry = StagingEntry { conflicted: true, ..StagingEntry::blob(&path, &hash, content.len() as u64) };
+                    staging_area.entries.insert(path.clone(), entry);
+                    result.conflicts.push(path);
+                }
+            }
+        }
+
+        staging_area.last_updated = Utc::now();
+        diff_engine.write_staging_area(lock, &staging_area)?;
+        Ok(result)
+    }
+
+    /// Resultado do merge de três vias de cada caminho que muda em relação a `ours`
+    fn resolve_paths(
+        &self,
+        base: &BTreeMap<String, String>,
+        ours: &BTreeMap<String, String>,
+        theirs: &BTreeMap<String, String>,
+        theirs_label: &str,
+    ) -> Result<Vec<(String, Resolution)>, CogitError> {
+        let paths: BTreeSet<&String> = base.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+
+        let mut resolutions = Vec::new();
+        for path in paths {
+            let (base_hash, ours_hash, theirs_hash) = (base.get(path), ours.get(path), theirs.get(path));
+            if ours_hash == theirs_hash || base_hash == theirs_hash {
+                continue;
+            }
+
+            let resolution = if base_hash == ours_hash {
+                Resolution::Clean(theirs_hash.cloned())
+            } else {
+                self.merge_file(base_hash, ours_hash, theirs_hash, theirs_label)?
+            };
+            resolutions.push((path.clone(), resolution));
+        }
+        Ok(resolutions)
+    }
+
+    /// Merge de um arquivo alterado dos dois lados
+    fn merge_file(
+        &self,
+        base_hash: Option<&String>,
+        ours_hash: Option<&String>,
+        theirs_hash: Option<&String>,
+        theirs_label: &str,
+    ) -> Result<Resolution, CogitError> {
+        match (ours_hash, theirs_hash) {
+            (Some(ours_hash), Some(theirs_hash)) => {
+                // Arquivo adicionado dos dois lados: a base é vazia
+                let base = match base_hash {
+                    Some(hash) => self.load_object(hash)?,
+                    None => Vec::new(),
+                };
+                let ours = self.load_object(ours_hash)?;
+                let theirs = self.load_object(theirs_hash)?;
+
+                if is_binary(&base) || is_binary(&ours) || is_binary(&theirs) {
+                    return Ok(Resolution::Conflict { content: ours, hash: ours_hash.clone() });
+                }
+
+                let merged = merge_text(&base, &ours, &theirs, "HEAD", theirs_label);
+                if merged.conflicts == 0 {
+                    Ok(Resolution::Clean(Some(self.store_object(ObjectType::Blob, &merged.content)?)))
+                } else {
+                    Ok(Resolution::Conflict { content: merged.content, hash: ours_hash.clone() })
+                }
+            }
+            // Modificado de um lado e removido do outro: a versão modificada fica no disco
+            (Some(hash), None) | (None, Some(hash)) => Ok(Resolution::Conflict {
+                content: self.load_object(hash)?,
+                hash: hash.clone(),
+            }),
+            (None, None) => Ok(Resolution::Clean(None)),
+        }
+    }
+
+    /// Verifica se há um merge esperando o commit final
+    pub fn merge_in_progress(&self) -> bool {
+        self.cogit_dir().join(MERGE_HEAD_FILE).is_file()
+    }
+
+    /// Commits sendo integrados pelo merge em andamento (pais extras do próximo commit)
+    pub fn merge_heads(&self) -> Result<Vec<String>, CogitError> {
+        let path = self.cogit_dir().join(MERGE_HEAD_FILE);
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// Mensagem sugerida para o commit do merge em andamento
+    pub fn merge_message(&self) -> Result<Option<String>, CogitError> {
+        let path = self.cogit_dir().join(MERGE_MSG_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?.trim_end().to_string()))
+    }
+
+    /// Remove o estado do merge em andamento
+    pub fn clear_merge_state(&self) -> Result<(), CogitError> {
+        for file in [MERGE_HEAD_FILE, MERGE_MSG_FILE] {
+            let path = self.cogit_dir().join(file);
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+        let merged = merge_text(base.as_bytes(), ours.as_bytes(), theirs.as_bytes(), "HEAD", "outro");
+        (String::from_utf8(merged.content).unwrap(), merged.conflicts)
+    }
+
+    #[test]
+    fn merges_changes_to_different_lines() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "A\nb\nc\nd\ne\n";
+        let theirs = "a\nb\nc\nd\nE\n";
+        assert_eq!(merge(base, ours, theirs), ("A\nb\nc\nd\nE\n".to_string(), 0));
+    }
+
+    #[test]
+    fn merges_one_sided_insertions_and_deletions() {
+        let base = "a\nb\nc\nd\ne\n";
+        let ours = "a\nb\nnovo\nc\nd\ne\n";
+        let theirs = "a\nb\nc\nd\n";
+        assert_eq!(merge(base, ours, theirs), ("a\nb\nnovo\nc\nd\n".to_string(), 0));
+    }
+
+    #[test]
+    fn identical_changes_on_both_sides_are_not_conflicts() {
+        let base = "a\nb\nc\n";
+        let both = "a\nB\nc\n";
+        assert_eq!(merge(base, both, both), (both.to_string(), 0));
+    }
+
+    #[test]
+    fn overlapping_changes_conflict_with_markers() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nnosso\nc\n";
+        let theirs = "a\ndeles\nc\n";
+        assert_eq!(
+            merge(base, ours, theirs),
+            ("a\n<<<<<<< HEAD\nnosso\n=======\ndeles\n>>>>>>> outro\nc\n".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn adjacent_changes_conflict() {
+        let base = "a\nb\nc\n";
+        let ours = "A\nb\nc\n";
+        let theirs = "a\nB\nc\n";
+        let (content, conflicts) = merge(base, ours, theirs);
+        assert_eq!(conflicts, 1);
+        assert!(content.starts_with("<<<<<<< HEAD\nA\nb\n=======\na\nB\n>>>>>>> outro\n"), "{}", content);
+    }
+
+    #[test]
+    fn counts_each_conflicting_block() {
+        let base = "1\n2\n3\n4\n5\n6\n7\n";
+        let ours = "x\n2\n3\n4\n5\n6\ny\n";
+        let theirs = "z\n2\n3\n4\n5\n6\nw\n";
+        let (content, conflicts) = merge(base, ours, theirs);
+        assert_eq!(conflicts, 2);
+        assert_eq!(content.matches(MARKER_SEPARATOR).count(), 2);
+        assert!(content.contains("\n2\n3\n4\n5\n6\n"));
+    }
+
+    #[test]
+    fn delete_against_edit_conflicts_with_empty_side() {
+        let base = "a\nb\nc\n";
+        let ours = "a\nc\n";
+        let theirs = "a\nB\nc\n";
+        assert_eq!(
+            merge(base, ours, theirs),
+            ("a\n<<<<<<< HEAD\n=======\nB\n>>>>>>> outro\nc\n".to_string(), 1)
+        );
+    }
+
+    #[test]
+    fn terminates_conflicting_lines_without_final_newline() {
+        let (content, conflicts) = merge("a", "b", "c");
+        assert_eq!(conflicts, 1);
+        assert_eq!(content, "<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> outro\n");
+    }
+}
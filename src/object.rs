@@ -0,0 +1,126 @@
+use std::io::{Read, Write};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::cogit::{CogitError, CogitRepository};
+
+/// Tipo de um objeto armazenado, gravado no cabeçalho do formato enquadrado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectType {
+    Blob,    // Conteúdo de um arquivo
+    Tree,    // Lista de entradas de um diretório
+    Commit,  // Metadados de um commit
+    Tag,     // Tag anotada: objeto marcado, autor da tag e mensagem
+}
+
+impl ObjectType {
+    /// Nome do tipo usado no cabeçalho
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectType::Blob => "blob",
+            ObjectType::Tree => "tree",
+            ObjectType::Commit => "commit",
+            ObjectType::Tag => "tag",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "blob" => Some(ObjectType::Blob),
+            "tree" => Some(ObjectType::Tree),
+            "commit" => Some(ObjectType::Commit),
+            "tag" => Some(ObjectType::Tag),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Nome de um objeto: SHA-256 dos bytes enquadrados `<tipo> <tamanho>\0<conteúdo>`
+///
+/// Incluir o tipo no hash impede que objetos de tipos diferentes com o mesmo
+/// conteúdo (um blob `[]` e a árvore vazia, por exemplo) tenham o mesmo nome.
+pub fn object_hash(object_type: ObjectType, content: &[u8]) -> String {
+    let mut framed = format!("{} {}\0", object_type, content.len()).into_bytes();
+    framed.extend_from_slice(content);
+    CogitRepository::calculate_hash(&framed)
+}
+
+/// Verifica se um hash é o nome de um objeto com esse tipo e conteúdo
+///
+/// Objetos gravados antes de o nome incluir o tipo são nomeados pelo hash do
+/// conteúdo bruto e continuam válidos.
+pub fn hash_matches(hash: &str, object_type: ObjectType, content: &[u8]) -> bool {
+    object_hash(object_type, content) == hash || CogitRepository::calculate_hash(content) == hash
+}
+
+/// Codifica um objeto no formato enquadrado: `<tipo> <tamanho>\0<conteúdo>`,
+/// comprimido com zlib
+pub fn encode_object(object_type: ObjectType, content: &[u8]) -> Result<Vec<u8>, CogitError> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(format!("{} {}\0", object_type, content.len()).as_bytes())?;
+    encoder.write_all(content)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decodifica um objeto do disco, validando cabeçalho, tamanho e hash
+///
+/// Um arquivo cujo hash bruto bate com o nome é um objeto legado, gravado antes
+/// do formato enquadrado; nesse caso o tipo é inferido pelo conteúdo.
+pub fn decode_object(hash: &str, data: &[u8]) -> Result<(ObjectType, Vec<u8>), CogitError> {
+    if CogitRepository::calculate_hash(data) == hash {
+        return Ok((infer_legacy_type(data), data.to_vec()));
+    }
+
+    let corrupt = |reason: &str| CogitError::CorruptObject(hash.to_string(), reason.to_string());
+
+    let mut decoded = Vec::new();
+    ZlibDecoder::new(data)
+        .read_to_end(&mut decoded)
+        .map_err(|_| corrupt("falha ao descomprimir"))?;
+
+    let header_end = decoded
+        .iter()
+        .position(|&byte| byte == 0)
+        .ok_or_else(|| corrupt("cabeçalho ausente"))?;
+    let header = std::str::from_utf8(&decoded[..header_end]).map_err(|_| corrupt("cabeçalho inválido"))?;
+
+    let (type_name, size) = header.split_once(' ').ok_or_else(|| corrupt("cabeçalho inválido"))?;
+    let object_type = ObjectType::parse(type_name).ok_or_else(|| corrupt("tipo de objeto desconhecido"))?;
+    let size: usize = size.parse().map_err(|_| corrupt("tamanho inválido no cabeçalho"))?;
+
+    let content = decoded.split_off(header_end + 1);
+    if content.len() != size {
+        return Err(corrupt("tamanho não confere com o cabeçalho"));
+    }
+    if !hash_matches(hash, object_type, &content) {
+        return Err(corrupt("hash não confere com o conteúdo"));
+    }
+
+    Ok((object_type, content))
+}
+
+/// Infere o tipo de um objeto legado (sem cabeçalho) pelo seu conteúdo JSON
+fn infer_legacy_type(data: &[u8]) -> ObjectType {
+    match serde_json::from_slice::<Value>(data) {
+        Ok(Value::Object(map)) if map.contains_key("tree_hash") => ObjectType::Commit,
+        Ok(Value::Array(entries))
+            if entries.iter().all(|entry| {
+                entry.get("name").is_some() && entry.get("hash").is_some() && entry.get("is_file").is_some()
+            }) =>
+        {
+            ObjectType::Tree
+        }
+        _ => ObjectType::Blob,
+    }
+}
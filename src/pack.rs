@@ -0,0 +1,726 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::lockfile::write_atomic;
+use crate::object::{hash_matches, ObjectType};
+use crate::refs::is_full_hash;
+
+/// Assinatura no início de todo arquivo .pack
+const PACK_SIGNATURE: &[u8] = b"CPCK";
+
+/// Versão do formato de pack
+const PACK_VERSION: u32 = 1;
+
+/// Assinatura no início de todo arquivo .idx binário
+const INDEX_SIGNATURE: &[u8] = b"CIDX";
+
+/// Versão do índice: a 1 era JSON (ainda lida), a 2 é a tabela binária
+const INDEX_VERSION: u32 = 2;
+
+/// Hash bruto (32 bytes), tipo/flags (1), offset (8), tamanho (8) e base do delta (4)
+const INDEX_ENTRY_SIZE: usize = 32 + 1 + 8 + 8 + 4;
+
+/// Flags do byte de tipo de uma entrada do índice
+const INDEX_LEGACY: u8 = 0x10;
+const INDEX_DELTA: u8 = 0x20;
+
+/// Tamanho máximo de uma cadeia de deltas (limita o custo de leitura)
+const MAX_DELTA_DEPTH: usize = 10;
+
+/// Quantos objetos anteriores são testados como base de delta
+const DELTA_WINDOW: usize = 10;
+
+/// Tamanho dos blocos usados para encontrar trechos em comum com a base
+const DELTA_BLOCK: usize = 16;
+
+/// Objetos menores que isso são sempre gravados inteiros
+const MIN_DELTA_SIZE: usize = 64;
+
+/// Instruções de um delta
+const DELTA_COPY: u8 = 0;    // copia `len` bytes da base a partir de `offset`
+const DELTA_INSERT: u8 = 1;  // insere `len` bytes literais
+
+/// Entrada do índice de um pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackEntry {
+    pub object_type: ObjectType,
+    pub offset: u64,                  // Posição do registro comprimido no .pack
+    pub length: u64,                  // Tamanho do registro comprimido
+    #[serde(default)]
+    pub delta_base: Option<String>,   // Presente quando o registro é um delta
+    #[serde(default)]
+    pub legacy: bool,                 // Objeto legado: o tipo foi apenas inferido
+}
+
+/// Índice de um pack (arquivo .idx ao lado do .pack)
+///
+/// No disco é uma tabela binária ordenada por hash (veja `encode_index`);
+/// índices JSON de versões anteriores continuam sendo lidos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackIndex {
+    pub version: u32,
+    pub entries: BTreeMap<String, PackEntry>,
+}
+
+/// Um pack carregado: caminho do .pack e o seu índice
+#[derive(Debug, Clone)]
+pub struct Pack {
+    pub pack_path: PathBuf,
+    pub index: PackIndex,
+}
+
+/// Resultado de um repack
+#[derive(Debug, Clone)]
+pub struct RepackStats {
+    pub objects: usize,
+    pub deltas: usize,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub pack_name: Option<String>,
+}
+
+impl CogitRepository {
+    /// Diretório dos packs (objects/pack)
+    pub fn pack_dir(&self) -> PathBuf {
+        self.cogit_dir().join("objects").join("pack")
+    }
+
+    /// Lista os objetos soltos (um arquivo por objeto em objects/xx/)
+    pub fn loose_objects(&self) -> Result<Vec<String>, CogitError> {
+        let mut hashes = Vec::new();
+        let objects_dir = self.cogit_dir().join("objects");
+
+        for dir_entry in fs::read_dir(objects_dir)? {
+            let dir_entry = dir_entry?;
+            let prefix = dir_entry.file_name().to_string_lossy().to_string();
+            if prefix.len() != 2 || !dir_entry.path().is_dir() {
+                continue;
+            }
+
+            for entry in fs::read_dir(dir_entry.path())? {
+                let hash = format!("{}{}", prefix, entry?.file_name().to_string_lossy());
+                if is_full_hash(&hash) {
+                    hashes.push(hash);
+                }
+            }
+        }
+
+        hashes.sort();
+        Ok(hashes)
+    }
+
+    /// Lista os objetos presentes em packs
+    pub fn packed_objects(&self) -> Result<Vec<String>, CogitError> {
+        self.with_packs(|packs| {
+            let mut hashes: Vec<String> = packs
+                .iter()
+                .flat_map(|pack| pack.index.entries.keys().cloned())
+                .collect();
+            hashes.sort();
+            hashes.dedup();
+            hashes
+        })
+    }
+
+    /// Localiza um objeto nos packs
+    pub fn packed_entry(&self, hash: &str) -> Result<Option<(PathBuf, PackEntry)>, CogitError> {
+        self.with_packs(|packs| {
+            packs.iter().find_map(|pack| {
+                pack.index
+                    .entries
+                    .get(hash)
+                    .map(|entry| (pack.pack_path.clone(), entry.clone()))
+            })
+        })
+    }
+
+    /// Lê um objeto de um pack, resolvendo cadeias de deltas (None se não está em pack)
+    pub fn read_packed_object(&self, hash: &str) -> Result<Option<(ObjectType, Vec<u8>)>, CogitError> {
+        let (pack_path, entry) = match self.packed_entry(hash)? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let corrupt = |reason: &str| CogitError::CorruptObject(hash.to_string(), reason.to_string());
+
+        // Um índice corrompido não pode fazer alocar mais do que o pack contém
+        let mut pack_file = fs::File::open(&pack_path)?;
+        let pack_len = pack_file.metadata()?.len();
+        if entry.offset.checked_add(entry.length).is_none_or(|end| end > pack_len) {
+            return Err(corrupt("registro fora dos limites do pack"));
+        }
+
+        let mut record = vec![0u8; entry.length as usize];
+        pack_file.seek(SeekFrom::Start(entry.offset))?;
+        pack_file
+            .read_exact(&mut record)
+            .map_err(|_| corrupt("registro truncado no pack"))?;
+
+        let mut data = Vec::new();
+        ZlibDecoder::new(record.as_slice())
+            .read_to_end(&mut data)
+            .map_err(|_| corrupt("falha ao descomprimir registro do pack"))?;
+
+        let content = match &entry.delta_base {
+            Some(base_hash) => {
+                let (_, base) = self.read_object(base_hash)?;
+                apply_delta(&base, &data).ok_or_else(|| corrupt("delta inválido"))?
+            }
+            None => data,
+        };
+
+        if !hash_matches(hash, entry.object_type, &content) {
+            return Err(corrupt("hash não confere com o conteúdo"));
+        }
+
+        Ok(Some((entry.object_type, content)))
+    }
+
+    /// Empacota todos os objetos (soltos e de packs anteriores) em um único pack
+    ///
+    /// Objetos do mesmo tipo e tamanho parecido são gravados como deltas uns dos
+    /// outros; ao final os objetos soltos empacotados e os packs antigos são
+    /// removidos. Com `keep`, apenas esses objetos entram no pack: os demais
+    /// objetos soltos ficam como estão e os que só existiam em packs são descartados.
+    /// Se ainda não há packs e o novo ficaria maior que os objetos soltos, nada muda.
+    pub fn repack(&self, keep: Option<&BTreeSet<String>>) -> Result<RepackStats, CogitError> {
+        let selected = |hash: &String| keep.is_none_or(|keep| keep.contains(hash));
+
+        let loose: Vec<String> = self.loose_objects()?.into_iter().filter(|hash| selected(hash)).collect();
+        let old_packs: Vec<PathBuf> = self.with_packs(|packs| packs.iter().map(|pack| pack.pack_path.clone()).collect())?;
+
+        let mut size_before = 0;
+        for hash in &loose {
+            size_before += fs::metadata(self.loose_object_path(hash))?.len();
+        }
+        for pack_path in &old_packs {
+            size_before += fs::metadata(pack_path)?.len();
+            size_before += fs::metadata(pack_path.with_extension("idx")).map_or(0, |meta| meta.len());
+        }
+
+        let mut hashes = loose.clone();
+        hashes.extend(self.packed_objects()?.into_iter().filter(|hash| selected(hash)));
+        hashes.sort();
+        hashes.dedup();
+
+        let mut objects = Vec::with_capacity(hashes.len());
+        let mut legacy = BTreeSet::new();
+        for hash in hashes {
+            let (object_type, content) = self.read_object(&hash)?;
+            if self.is_legacy_object(&hash)? {
+                legacy.insert(hash.clone());
+            }
+            objects.push((hash, object_type, content));
+        }
+
+        if objects.is_empty() {
+            self.remove_packs(&old_packs)?;
+            let size_after = if old_packs.is_empty() { size_before } else { 0 };
+            return Ok(RepackStats { objects: 0, deltas: 0, size_before, size_after, pack_name: None });
+        }
+
+        // Como no git: agrupa por tipo e grava primeiro os maiores, que viram bases
+        objects.sort_by(|a, b| {
+            type_order(a.1)
+                .cmp(&type_order(b.1))
+                .then(b.2.len().cmp(&a.2.len()))
+                .then(a.0.cmp(&b.0))
+        });
+
+        let mut pack_data = Vec::new();
+        pack_data.extend_from_slice(PACK_SIGNATURE);
+        pack_data.extend_from_slice(&PACK_VERSION.to_be_bytes());
+        pack_data.extend_from_slice(&(objects.len() as u32).to_be_bytes());
+
+        let mut entries = BTreeMap::new();
+        let mut depths = vec![0usize; objects.len()];
+        let mut deltas = 0;
+
+        for i in 0..objects.len() {
+            let (hash, object_type, content) = &objects[i];
+
+            let mut best: Option<(usize, Vec<u8>)> = None;
+            if content.len() >= MIN_DELTA_SIZE {
+                for j in i.saturating_sub(DELTA_WINDOW)..i {
+                    let (_, base_type, base) = &objects[j];
+                    if base_type != object_type || depths[j] >= MAX_DELTA_DEPTH {
+                        continue;
+                    }
+
+                    let delta = compute_delta(base, content);
+                    let limit = best.as_ref().map_or(content.len() / 2, |(_, current)| current.len());
+                    if delta.len() < limit {
+                        best = Some((j, delta));
+                    }
+                }
+            }
+
+            let (record, delta_base) = match best {
+                Some((j, delta)) => {
+                    depths[i] = depths[j] + 1;
+                    deltas += 1;
+                    (delta, Some(objects[j].0.clone()))
+                }
+                None => (content.clone(), None),
+            };
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&record)?;
+            let compressed = encoder.finish()?;
+
+            entries.insert(
+                hash.clone(),
+                PackEntry {
+                    object_type: *object_type,
+                    offset: pack_data.len() as u64,
+                    length: compressed.len() as u64,
+                    delta_base,
+                    legacy: legacy.contains(hash),
+                },
+            );
+            pack_data.extend_from_slice(&compressed);
+        }
+
+        let pack_name = format!("pack-{}", Self::calculate_hash(&pack_data));
+        let index = PackIndex { version: INDEX_VERSION, entries };
+        let index_data = encode_index(&index);
+
+        // Sem packs a substituir, um pack maior que os objetos soltos não compensa
+        let size_after = (pack_data.len() + index_data.len()) as u64;
+        if old_packs.is_empty() && size_after >= size_before {
+            return Ok(RepackStats { objects: objects.len(), deltas, size_before, size_after: size_before, pack_name: None });
+        }
+
+        // O .idx só aparece depois do .pack completo: um pack sem índice é ignorado
+        let pack_dir = self.pack_dir();
+        fs::create_dir_all(&pack_dir)?;
+        let pack_path = pack_dir.join(format!("{}.pack", pack_name));
+        write_atomic(&pack_path, &pack_data)?;
+        write_atomic(&pack_path.with_extension("idx"), &index_data)?;
+
+        let stale_packs: Vec<PathBuf> = old_packs.into_iter().filter(|path| *path != pack_path).collect();
+        self.remove_packs(&stale_packs)?;
+        for hash in &loose {
+            let object_path = self.loose_object_path(hash);
+            fs::remove_file(&object_path)?;
+            if let Some(object_dir) = object_path.parent() {
+                let _ = fs::remove_dir(object_dir);
+            }
+        }
+
+        Ok(RepackStats {
+            objects: objects.len(),
+            deltas,
+            size_before,
+            size_after,
+            pack_name: Some(pack_name),
+        })
+    }
+
+    /// Remove packs (e seus índices) e descarta o cache
+    fn remove_packs(&self, pack_paths: &[PathBuf]) -> Result<(), CogitError> {
+        for pack_path in pack_paths {
+            fs::remove_file(pack_path.with_extension("idx"))?;
+            fs::remove_file(pack_path)?;
+        }
+        self.invalidate_packs();
+        Ok(())
+    }
+
+    /// Executa uma consulta sobre os packs, carregando os índices na primeira vez
+    fn with_packs<T>(&self, query: impl FnOnce(&[Pack]) -> T) -> Result<T, CogitError> {
+        let mut cache = self.pack_cache().borrow_mut();
+        if cache.is_none() {
+            *cache = Some(load_packs(&self.pack_dir())?);
+        }
+        Ok(query(cache.as_deref().unwrap_or_default()))
+    }
+
+    /// Descarta os índices carregados (após criar ou remover packs)
+    pub fn invalidate_packs(&self) {
+        *self.pack_cache().borrow_mut() = None;
+    }
+}
+
+/// Carrega os índices de todos os packs de um diretório
+fn load_packs(pack_dir: &Path) -> Result<Vec<Pack>, CogitError> {
+    let mut packs = Vec::new();
+    if !pack_dir.is_dir() {
+        return Ok(packs);
+    }
+
+    for entry in fs::read_dir(pack_dir)? {
+        let index_path = entry?.path();
+        if index_path.extension().and_then(|ext| ext.to_str()) != Some("idx") {
+            continue;
+        }
+
+        let pack_path = index_path.with_extension("pack");
+        if !pack_path.is_file() {
+            continue;
+        }
+
+        let data = fs::read(&index_path)?;
+        let index = match data.starts_with(INDEX_SIGNATURE) {
+            true => decode_index(&data).ok_or_else(|| {
+                CogitError::CorruptObject(index_path.display().to_string(), "índice de pack inválido".to_string())
+            })?,
+            false => serde_json::from_slice(&data)?,
+        };
+        packs.push(Pack { pack_path, index });
+    }
+
+    packs.sort_by(|a, b| a.pack_path.cmp(&b.pack_path));
+    Ok(packs)
+}
+
+/// Serializa o índice: cabeçalho (assinatura, versão e número de entradas) e
+/// uma entrada de tamanho fixo por objeto, em ordem de hash; a base de um
+/// delta é gravada como a posição da sua entrada na tabela
+fn encode_index(index: &PackIndex) -> Vec<u8> {
+    let positions: HashMap<&str, u32> = index
+        .entries
+        .keys()
+        .enumerate()
+        .map(|(position, hash)| (hash.as_str(), position as u32))
+        .collect();
+
+    let mut data = Vec::with_capacity(12 + index.entries.len() * INDEX_ENTRY_SIZE);
+    data.extend_from_slice(INDEX_SIGNATURE);
+    data.extend_from_slice(&INDEX_VERSION.to_be_bytes());
+    data.extend_from_slice(&(index.entries.len() as u32).to_be_bytes());
+
+    for (hash, entry) in &index.entries {
+        data.extend_from_slice(&hash_to_bytes(hash));
+        let mut flags = type_code(entry.object_type);
+        if entry.legacy {
+            flags |= INDEX_LEGACY;
+        }
+        let base = match &entry.delta_base {
+            Some(base) => {
+                flags |= INDEX_DELTA;
+                positions[base.as_str()]
+            }
+            None => 0,
+        };
+        data.push(flags);
+        data.extend_from_slice(&entry.offset.to_be_bytes());
+        data.extend_from_slice(&entry.length.to_be_bytes());
+        data.extend_from_slice(&base.to_be_bytes());
+    }
+    data
+}
+
+/// Lê um índice binário (None se estiver truncado ou malformado)
+fn decode_index(data: &[u8]) -> Option<PackIndex> {
+    let version = u32::from_be_bytes(data.get(4..8)?.try_into().ok()?);
+    let count = u32::from_be_bytes(data.get(8..12)?.try_into().ok()?) as usize;
+    if version != INDEX_VERSION || data.len() != 12 + count * INDEX_ENTRY_SIZE {
+        return None;
+    }
+
+    let records: Vec<&[u8]> = data[12..].chunks_exact(INDEX_ENTRY_SIZE).collect();
+    let hashes: Vec<String> = records.iter().map(|record| bytes_to_hash(&record[..32])).collect();
+
+    let mut entries = BTreeMap::new();
+    for (hash, record) in hashes.iter().zip(&records) {
+        let flags = record[32];
+        let base = u32::from_be_bytes(record[49..53].try_into().ok()?) as usize;
+        let delta_base = match flags & INDEX_DELTA != 0 {
+            true => Some(hashes.get(base)?.clone()),
+            false => None,
+        };
+        entries.insert(
+            hash.clone(),
+            PackEntry {
+                object_type: type_from_code(flags & 0x0f)?,
+                offset: u64::from_be_bytes(record[33..41].try_into().ok()?),
+                length: u64::from_be_bytes(record[41..49].try_into().ok()?),
+                delta_base,
+                legacy: flags & INDEX_LEGACY != 0,
+            },
+        );
+    }
+    Some(PackIndex { version, entries })
+}
+
+/// Código do tipo gravado no índice
+fn type_code(object_type: ObjectType) -> u8 {
+    match object_type {
+        ObjectType::Blob => 1,
+        ObjectType::Tree => 2,
+        ObjectType::Commit => 3,
+        ObjectType::Tag => 4,
+    }
+}
+
+fn type_from_code(code: u8) -> Option<ObjectType> {
+    match code {
+        1 => Some(ObjectType::Blob),
+        2 => Some(ObjectType::Tree),
+        3 => Some(ObjectType::Commit),
+        4 => Some(ObjectType::Tag),
+        _ => None,
+    }
+}
+
+/// Converte um hash hexadecimal nos seus 32 bytes
+fn hash_to_bytes(hash: &str) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hash[i * 2..i * 2 + 2], 16).unwrap_or_default();
+    }
+    bytes
+}
+
+fn bytes_to_hash(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn type_order(object_type: ObjectType) -> u8 {
+    match object_type {
+        ObjectType::Commit => 0,
+        ObjectType::Tag => 1,
+        ObjectType::Tree => 2,
+        ObjectType::Blob => 3,
+    }
+}
+
+/// Calcula um delta que reconstrói `target` a partir de `base`
+///
+/// Formato: tamanho da base e do alvo (varints), seguido de instruções de
+/// cópia (offset e tamanho na base) e de inserção (bytes literais).
+pub fn compute_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut blocks: HashMap<&[u8], usize> = HashMap::new();
+    if base.len() >= DELTA_BLOCK {
+        for start in (0..=base.len() - DELTA_BLOCK).step_by(DELTA_BLOCK) {
+            blocks.entry(&base[start..start + DELTA_BLOCK]).or_insert(start);
+        }
+    }
+
+    let mut delta = Vec::new();
+    write_varint(&mut delta, base.len() as u64);
+    write_varint(&mut delta, target.len() as u64);
+
+    let mut pending = 0;
+    let mut position = 0;
+    while position + DELTA_BLOCK <= target.len() {
+        let Some(&block_start) = blocks.get(&target[position..position + DELTA_BLOCK]) else {
+            position += 1;
+            continue;
+        };
+
+        // Estende o trecho em comum para trás (dentro do que ainda seria inserido) e para frente
+        let (mut source, mut start) = (block_start, position);
+        while source > 0 && start > pending && base[source - 1] == target[start - 1] {
+            source -= 1;
+            start -= 1;
+        }
+        let mut length = position + DELTA_BLOCK - start;
+        while source + length < base.len() && start + length < target.len() && base[source + length] == target[start + length] {
+            length += 1;
+        }
+
+        write_insert(&mut delta, &target[pending..start]);
+        delta.push(DELTA_COPY);
+        write_varint(&mut delta, source as u64);
+        write_varint(&mut delta, length as u64);
+
+        position = start + length;
+        pending = position;
+    }
+    write_insert(&mut delta, &target[pending..]);
+
+    delta
+}
+
+/// Reconstrói o conteúdo a partir da base e de um delta (None se o delta é inválido)
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut cursor = 0;
+    let base_len = read_varint(delta, &mut cursor)? as usize;
+    let target_len = read_varint(delta, &mut cursor)? as usize;
+    if base_len != base.len() {
+        return None;
+    }
+
+    let mut target = Vec::with_capacity(target_len);
+    while cursor < delta.len() {
+        let instruction = delta[cursor];
+        cursor += 1;
+
+        match instruction {
+            DELTA_COPY => {
+                let offset = read_varint(delta, &mut cursor)? as usize;
+                let length = read_varint(delta, &mut cursor)? as usize;
+                target.extend_from_slice(base.get(offset..offset.checked_add(length)?)?);
+            }
+            DELTA_INSERT => {
+                let length = read_varint(delta, &mut cursor)? as usize;
+                target.extend_from_slice(delta.get(cursor..cursor.checked_add(length)?)?);
+                cursor += length;
+            }
+            _ => return None,
+        }
+    }
+
+    (target.len() == target_len).then_some(target)
+}
+
+fn write_insert(delta: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    delta.push(DELTA_INSERT);
+    write_varint(delta, bytes.len() as u64);
+    delta.extend_from_slice(bytes);
+}
+
+/// Inteiro sem sinal em LEB128 (7 bits por byte)
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn read_varint(buffer: &[u8], cursor: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *buffer.get(*cursor)?;
+        *cursor += 1;
+        if shift >= 64 {
+            return None;
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+        shift += 7;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(base: &[u8], target: &[u8]) -> Vec<u8> {
+        let delta = compute_delta(base, target);
+        assert_eq!(apply_delta(base, &delta).as_deref(), Some(target), "base {:?}, alvo {:?}", base, target);
+        delta
+    }
+
+    /// Conteúdo de teste sem blocos repetidos
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn delta_round_trips_at_block_boundaries() {
+        for len in [0, 1, DELTA_BLOCK - 1, DELTA_BLOCK, DELTA_BLOCK + 1, 2 * DELTA_BLOCK, 5 * DELTA_BLOCK + 3] {
+            let base = sample(len);
+            round_trip(&base, &base);
+            round_trip(&base, &[]);
+            round_trip(&[], &base);
+        }
+    }
+
+    #[test]
+    fn delta_round_trips_inserts_around_copies() {
+        let base = sample(10 * DELTA_BLOCK);
+
+        let mut prefixed = b"novo inicio".to_vec();
+        prefixed.extend_from_slice(&base);
+        let mut suffixed = base.clone();
+        suffixed.extend_from_slice(b"novo fim");
+        let mut middle = base[..5 * DELTA_BLOCK + 3].to_vec();
+        middle.extend_from_slice(b"meio");
+        middle.extend_from_slice(&base[5 * DELTA_BLOCK + 3..]);
+        let mut dropped = base[..DELTA_BLOCK + 5].to_vec();
+        dropped.extend_from_slice(&base[4 * DELTA_BLOCK + 1..]);
+
+        for target in [prefixed, suffixed, middle, dropped] {
+            let delta = round_trip(&base, &target);
+            assert!(delta.len() < target.len() / 2, "delta de {} bytes para {} bytes", delta.len(), target.len());
+        }
+    }
+
+    #[test]
+    fn delta_copies_blocks_out_of_order() {
+        let base = sample(4 * DELTA_BLOCK);
+        let mut target = base[2 * DELTA_BLOCK..].to_vec();
+        target.extend_from_slice(&base[..2 * DELTA_BLOCK]);
+
+        let delta = round_trip(&base, &target);
+        assert!(delta.iter().filter(|&&byte| byte == DELTA_COPY).count() >= 2);
+        assert!(delta.len() < target.len() / 2);
+    }
+
+    #[test]
+    fn apply_delta_rejects_invalid_deltas() {
+        let base = sample(3 * DELTA_BLOCK);
+        let mut target = base.clone();
+        target.extend_from_slice(b"extra");
+        let delta = compute_delta(&base, &target);
+
+        assert_eq!(apply_delta(&base[1..], &delta), None);
+        assert_eq!(apply_delta(&base, &delta[..delta.len() - 1]), None);
+
+        let mut bad_copy = Vec::new();
+        write_varint(&mut bad_copy, base.len() as u64);
+        write_varint(&mut bad_copy, 4);
+        bad_copy.push(DELTA_COPY);
+        write_varint(&mut bad_copy, base.len() as u64 - 2);
+        write_varint(&mut bad_copy, 4);
+        assert_eq!(apply_delta(&base, &bad_copy), None);
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buffer = Vec::new();
+            write_varint(&mut buffer, value);
+            let mut cursor = 0;
+            assert_eq!(read_varint(&buffer, &mut cursor), Some(value));
+            assert_eq!(cursor, buffer.len());
+        }
+    }
+
+    #[test]
+    fn index_round_trips_through_binary_format() {
+        let base = "a".repeat(64);
+        let delta = format!("0f{}", "1".repeat(62));
+        let mut entries = BTreeMap::new();
+        entries.insert(
+            base.clone(),
+            PackEntry { object_type: ObjectType::Blob, offset: 12, length: 40, delta_base: None, legacy: true },
+        );
+        entries.insert(
+            delta.clone(),
+            PackEntry { object_type: ObjectType::Blob, offset: 52, length: 9, delta_base: Some(base.clone()), legacy: false },
+        );
+        let index = PackIndex { version: INDEX_VERSION, entries };
+
+        let data = encode_index(&index);
+        assert_eq!(data.len(), 12 + 2 * INDEX_ENTRY_SIZE);
+
+        let decoded = decode_index(&data).expect("índice válido");
+        assert_eq!(decoded.entries.len(), 2);
+        assert!(decoded.entries[&base].legacy);
+        assert_eq!(decoded.entries[&delta].delta_base.as_deref(), Some(base.as_str()));
+        assert_eq!((decoded.entries[&delta].offset, decoded.entries[&delta].length), (52, 9));
+        assert!(decode_index(&data[..data.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::config::{Signature, SignatureRole};
+use crate::lockfile::write_atomic;
+use crate::refs::{collect_refs, remove_empty_parents, Head};
+
+/// Diretório dos reflogs, espelhando os nomes das referências (.cogit/logs)
+const LOGS_DIR: &str = "logs";
+
+/// Um movimento de referência registrado no reflog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReflogEntry {
+    pub old: Option<String>,  // Valor anterior (None se a referência não existia)
+    pub new: String,
+    pub committer: Signature, // Quem moveu a referência, e quando
+    pub message: String,      // Motivo: "commit: ...", "checkout: ...", "reset: ..."
+}
+
+impl CogitRepository {
+    /// Caminho do reflog de uma referência ("HEAD" ou "refs/heads/<nome>")
+    fn reflog_path(&self, ref_name: &str) -> PathBuf {
+        self.cogit_dir().join(LOGS_DIR).join(ref_name)
+    }
+
+    /// Acrescenta um movimento ao reflog de uma referência
+    ///
+    /// O reflog nunca impede a operação que o gerou: sem identidade configurada,
+    /// o movimento é registrado em nome de "desconhecido".
+    pub fn append_reflog(&self, ref_name: &str, old: Option<&str>, new: &str, message: &str) -> Result<(), CogitError> {
+        let now = Utc::now().trunc_subsecs(0);
+        let committer = self.signature(SignatureRole::Committer, now).unwrap_or_else(|_| Signature {
+            name: "desconhecido".to_string(),
+            email: "desconhecido".to_string(),
+            timestamp: now,
+        });
+        let entry = ReflogEntry {
+            old: old.map(|hash| hash.to_string()),
+            new: new.to_string(),
+            committer,
+            message: message.lines().next().unwrap_or_default().to_string(),
+        };
+
+        let path = self.reflog_path(ref_name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Uma linha JSON por movimento, gravada de uma vez no fim do arquivo
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(&line)?;
+        Ok(())
+    }
+
+    /// Movimentos de uma referência, do mais recente para o mais antigo
+    pub fn read_reflog(&self, ref_name: &str) -> Result<Vec<ReflogEntry>, CogitError> {
+        let path = self.reflog_path(ref_name);
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for line in fs::read_to_string(path)?.lines().filter(|line| !line.trim().is_empty()) {
+            entries.push(serde_json::from_str::<ReflogEntry>(line)?);
+        }
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Remove uma entrada do reflog (posição 0 = a mais recente), devolvendo-a
+    pub fn drop_reflog_entry(&self, ref_name: &str, position: usize) -> Result<ReflogEntry, CogitError> {
+        let mut entries = self.read_reflog(ref_name)?;
+        if position >= entries.len() {
+            return Err(CogitError::UnknownRevision(format!("{}@{{{}}}", ref_name, position)));
+        }
+        let removed = entries.remove(position);
+
+        // O arquivo guarda as entradas da mais antiga para a mais recente
+        let mut content = Vec::new();
+        for entry in entries.iter().rev() {
+            content.extend(serde_json::to_vec(entry)?);
+            content.push(b'\n');
+        }
+        write_atomic(&self.reflog_path(ref_name), &content)?;
+        Ok(removed)
+    }
+
+    /// Remove o reflog de uma referência apagada
+    pub fn delete_reflog(&self, ref_name: &str) -> Result<(), CogitError> {
+        let path = self.reflog_path(ref_name);
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            remove_empty_parents(&path, &self.cogit_dir().join(LOGS_DIR));
+        }
+        Ok(())
+    }
+
+    /// Referências que têm reflog ("HEAD" e "refs/..."), em ordem
+    pub fn list_reflogs(&self) -> Result<Vec<String>, CogitError> {
+        let mut names = Vec::new();
+        let logs_dir = self.cogit_dir().join(LOGS_DIR);
+        if logs_dir.is_dir() {
+            collect_refs(&logs_dir, "", &mut names)?;
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Nome do reflog correspondente a uma referência escrita pelo usuário
+    ///
+    /// `HEAD` e referências completas valem como estão; um nome curto é
+    /// procurado entre os branches e depois em refs/ (como `stash`). Vazio
+    /// (`@{n}`) é o branch atual, ou o HEAD se estiver destacado.
+    pub fn reflog_name(&self, name: &str) -> Result<Option<String>, CogitError> {
+        if name.is_empty() {
+            return Ok(Some(match self.read_head()? {
+                Head::Branch(branch) => format!("refs/heads/{}", branch),
+                Head::Detached(_) => "HEAD".to_string(),
+            }));
+        }
+        if name == "HEAD" {
+            return Ok(Some(name.to_string()));
+        }
+
+        let candidates = [
+            name.to_string(),
+            format!("refs/heads/{}", name),
+            format!("refs/{}", name),
+        ];
+        Ok(candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with("refs/") && !candidate.contains(".."))
+            .find(|candidate| self.reflog_path(candidate).is_file() || self.cogit_dir().join(candidate).is_file()))
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::lockfile::{LockFile, LOCK_SUFFIX};
+
+/// Estado do HEAD: aponta para um branch (simbólico) ou direto para um commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Head {
+    Branch(String),    // ref: refs/heads/<nome>
+    Detached(String),  // hash do commit
+}
+
+/// Prefixo das referências de branches
+const HEADS_PREFIX: &str = "refs/heads/";
+
+impl CogitRepository {
+    /// Lê o HEAD e resolve se é simbólico ou destacado
+    pub fn read_head(&self) -> Result<Head, CogitError> {
+        let content = fs::read_to_string(self.cogit_dir().join("HEAD"))?;
+        let content = content.trim();
+
+        match content.strip_prefix("ref:") {
+            Some(reference) => {
+                let reference = reference.trim();
+                let branch = reference
+                    .strip_prefix(HEADS_PREFIX)
+                    .ok_or_else(|| CogitError::InvalidRefName(reference.to_string()))?;
+                Ok(Head::Branch(branch.to_string()))
+            }
+            None if is_full_hash(content) => Ok(Head::Detached(content.to_string())),
+            None => Err(CogitError::InvalidRefName(content.to_string())),
+        }
+    }
+
+    /// Nome do branch atual, ou None com HEAD destacado
+    pub fn current_branch(&self) -> Result<Option<String>, CogitError> {
+        match self.read_head()? {
+            Head::Branch(name) => Ok(Some(name)),
+            Head::Detached(_) => Ok(None),
+        }
+    }
+
+    /// Hash do commit apontado pelo HEAD, ou None se o branch ainda não tem commits
+    pub fn head_commit(&self) -> Result<Option<String>, CogitError> {
+        match self.read_head()? {
+            Head::Branch(name) => self.read_branch(&name),
+            Head::Detached(hash) => Ok(Some(hash)),
+        }
+    }
+
+    /// Descrição curta do HEAD para mensagens: o branch atual ou o commit destacado
+    pub fn head_description(&self) -> Result<String, CogitError> {
+        match self.read_head()? {
+            Head::Branch(name) => Ok(name),
+            Head::Detached(hash) => Ok(hash[..7].to_string()),
+        }
+    }
+
+    /// Avança o que o HEAD aponta (o branch atual, ou o próprio HEAD se destacado),
+    /// registrando o motivo no reflog
+    ///
+    /// `expected` é o commit sobre o qual a operação foi feita (None num branch
+    /// sem commits): se a referência mudou nesse meio-tempo, nada é gravado.
+    pub fn update_head_commit(&self, expected: Option<&str>, hash: &str, reason: &str) -> Result<(), CogitError> {
+        match self.read_head()? {
+            Head::Branch(name) => self.write_branch(&name, expected, hash, reason),
+            Head::Detached(_) => self.write_head_detached(expected, hash, reason),
+        }
+    }
+
+    /// Faz o HEAD apontar simbolicamente para um branch
+    pub fn set_head_branch(&self, name: &str, reason: &str) -> Result<(), CogitError> {
+        let old = self.head_commit().ok().flatten();
+        let content = format!("ref: {}{}\n", HEADS_PREFIX, name);
+        LockFile::acquire(&self.cogit_dir().join("HEAD"))?.commit(content.as_bytes())?;
+
+        // Um branch sem commits ainda não tem o que registrar
+        match self.read_branch(name)? {
+            Some(new) => self.append_reflog("HEAD", old.as_deref(), &new, reason),
+            None => Ok(()),
+        }
+    }
+
+    /// Destaca o HEAD, apontando diretamente para um commit
+    pub fn set_head_detached(&self, hash: &str, reason: &str) -> Result<(), CogitError> {
+        let old = self.head_commit().ok().flatten();
+        self.write_head_detached(old.as_deref(), hash, reason)
+    }
+
+    /// Grava o HEAD destacado, conferindo sob o lock que ele ainda aponta para `expected`
+    fn write_head_detached(&self, expected: Option<&str>, hash: &str, reason: &str) -> Result<(), CogitError> {
+        let lock = LockFile::acquire(&self.cogit_dir().join("HEAD"))?;
+        let old = self.head_commit().ok().flatten();
+        if old.as_deref() != expected {
+            return Err(CogitError::RefChanged("HEAD".to_string()));
+        }
+
+        lock.commit(format!("{}\n", hash).as_bytes())?;
+        self.append_reflog("HEAD", old.as_deref(), hash, reason)
+    }
+
+    /// Lê o commit de um branch, ou None se o branch não existe
+    pub fn read_branch(&self, name: &str) -> Result<Option<String>, CogitError> {
+        let path = self.branch_path(name)?;
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?.trim().to_string()))
+    }
+
+    /// Grava o commit de um branch (sob lock em refs/heads/<nome>.lock)
+    ///
+    /// Já com o lock, o valor atual é relido e precisa ser `expected` (None para
+    /// um branch novo); assim dois commits simultâneos não se sobrescrevem. O
+    /// movimento vai para o reflog do branch e, se ele for o atual, também
+    /// para o do HEAD.
+    fn write_branch(&self, name: &str, expected: Option<&str>, hash: &str, reason: &str) -> Result<(), CogitError> {
+        let lock = LockFile::acquire(&self.branch_path(name)?)?;
+        let old = self.read_branch(name)?;
+        if old.as_deref() != expected {
+            return Err(CogitError::RefChanged(format!("{}{}", HEADS_PREFIX, name)));
+        }
+        lock.commit(hash.as_bytes())?;
+
+        self.append_reflog(&format!("{}{}", HEADS_PREFIX, name), old.as_deref(), hash, reason)?;
+        if self.current_branch()?.as_deref() == Some(name) {
+            self.append_reflog("HEAD", old.as_deref(), hash, reason)?;
+        }
+        Ok(())
+    }
+
+    /// Caminho do arquivo de um branch (refs/heads/<nome>), recusando nomes
+    /// inválidos (como `..`) que levariam para fora de refs/heads
+    fn branch_path(&self, name: &str) -> Result<PathBuf, CogitError> {
+        validate_ref_name(name)?;
+        Ok(self.cogit_dir().join(HEADS_PREFIX).join(name))
+    }
+
+    /// Lista os branches existentes (nomes com '/' incluídos), em ordem
+    pub fn list_branches(&self) -> Result<Vec<String>, CogitError> {
+        let mut branches = Vec::new();
+        let heads_dir = self.cogit_dir().join(HEADS_PREFIX);
+        if heads_dir.is_dir() {
+            collect_refs(&heads_dir, "", &mut branches)?;
+        }
+        branches.sort();
+        Ok(branches)
+    }
+
+    /// Lista todas as referências em refs/ (nome completo e conteúdo), em ordem
+    pub fn list_refs(&self) -> Result<Vec<(String, String)>, CogitError> {
+        let mut names = Vec::new();
+        let refs_dir = self.cogit_dir().join("refs");
+        if refs_dir.is_dir() {
+            collect_refs(&refs_dir, "refs", &mut names)?;
+        }
+        names.sort();
+
+        let mut refs = Vec::new();
+        for name in names {
+            let value = fs::read_to_string(self.cogit_dir().join(&name))?.trim().to_string();
+            refs.push((name, value));
+        }
+        Ok(refs)
+    }
+
+    /// Cria um branch apontando para um commit
+    pub fn create_branch(&self, name: &str, commit_hash: &str) -> Result<(), CogitError> {
+        if self.read_branch(name)?.is_some() {
+            return Err(CogitError::BranchExists(name.to_string()));
+        }
+
+        self.write_branch(name, None, commit_hash, &format!("branch: criado em {}", &commit_hash[..7]))
+    }
+
+    /// Remove um branch
+    ///
+    /// Sem `force`, recusa remover um branch cujos commits não são alcançáveis
+    /// a partir do HEAD, para não perder histórico.
+    pub fn delete_branch(&self, name: &str, force: bool) -> Result<(), CogitError> {
+        let commit_hash = self
+            .read_branch(name)?
+            .ok_or_else(|| CogitError::BranchNotFound(name.to_string()))?;
+
+        if self.current_branch()?.as_deref() == Some(name) {
+            return Err(CogitError::InvalidRefName(format!(
+                "não é possível remover o branch atual '{}'",
+                name
+            )));
+        }
+
+        if !force {
+            let merged = match self.head_commit()? {
+                Some(head) => self.is_ancestor(&commit_hash, &head)?,
+                None => false,
+            };
+            if !merged {
+                return Err(CogitError::InvalidRefName(format!(
+                    "o branch '{}' não está integrado ao HEAD; use -D para remover mesmo assim",
+                    name
+                )));
+            }
+        }
+
+        let path = self.branch_path(name)?;
+        fs::remove_file(&path)?;
+        remove_empty_parents(&path, &self.cogit_dir().join(HEADS_PREFIX));
+        self.delete_reflog(&format!("{}{}", HEADS_PREFIX, name))
+    }
+
+    /// Verifica se `ancestor` é alcançável seguindo os pais de `descendant`
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool, CogitError> {
+        let mut visited = HashSet::new();
+        let mut pending = vec![descendant.to_string()];
+
+        while let Some(hash) = pending.pop() {
+            if hash == ancestor {
+                return Ok(true);
+            }
+            if visited.insert(hash.clone()) {
+                pending.extend(self.load_commit(&hash)?.parents);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Verifica se a string é um hash SHA-256 completo em hexadecimal
+pub fn is_full_hash(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Valida um nome de referência (regras semelhantes às do git check-ref-format)
+pub fn validate_ref_name(name: &str) -> Result<(), CogitError> {
+    let invalid = name.is_empty()
+        || name == "HEAD"
+        || name.starts_with('-')
+        || name.starts_with('/')
+        || name.ends_with('/')
+        || name.ends_with('.')
+        || name.ends_with(".lock")
+        || name.contains("..")
+        || name.contains("//")
+        || name.contains("@{")
+        || name.split('/').any(|part| part.starts_with('.'))
+        || name
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "~^:?*[\\".contains(c));
+
+    if invalid {
+        return Err(CogitError::InvalidRefName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Coleta recursivamente os nomes de referências de um diretório
+pub fn collect_refs(dir: &Path, prefix: &str, refs: &mut Vec<String>) -> Result<(), CogitError> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // Locks e temporários de escritas em andamento não são referências
+        if name.starts_with('.') || name.ends_with(LOCK_SUFFIX) {
+            continue;
+        }
+
+        let full_name = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+
+        if path.is_dir() {
+            collect_refs(&path, &full_name, refs)?;
+        } else if path.is_file() {
+            refs.push(full_name);
+        }
+    }
+    Ok(())
+}
+
+/// Remove diretórios vazios deixados por um branch (ou tag) com '/' no nome
+pub fn remove_empty_parents(path: &Path, stop_at: &Path) {
+    let mut current = path.parent();
+    while let Some(dir) = current {
+        if dir == stop_at || fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::{path_matches, DiffEngine, StagingArea, StagingEntry};
+use crate::lockfile::LockFile;
+
+/// O que `cogit reset <rev>` reescreve além do branch atual
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    Soft,   // Só o branch: o index e o working tree continuam como estavam
+    Mixed,  // Branch e index (padrão): as mudanças voltam a ficar não staged
+    Hard,   // Branch, index e working tree: mudanças rastreadas são descartadas
+}
+
+impl CogitRepository {
+    /// Move o branch atual (ou o HEAD destacado) para um commit
+    ///
+    /// Com `Soft` o index mantém o conteúdo anterior, que passa a aparecer como
+    /// mudanças staged em relação ao novo HEAD. `Mixed` esvazia o staging area e
+    /// `Hard` também reescreve no working tree os arquivos rastreados; arquivos
+    /// não rastreados nunca são tocados. Um merge, cherry-pick ou revert em
+    /// andamento é descartado (exceto com `Soft`, que é recusado nesse caso).
+    pub fn reset(&self, target: &str, mode: ResetMode, label: &str) -> Result<(), CogitError> {
+        if mode == ResetMode::Soft {
+            if self.merge_in_progress() {
+                return Err(CogitError::OperationInProgress("merge".to_string()));
+            }
+            if let Some(kind) = self.pick_in_progress() {
+                return Err(CogitError::OperationInProgress(kind.name().to_string()));
+            }
+        }
+
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        let old_commit = self.head_commit()?;
+        let old_head = self.head_files()?;
+        let old_index = self.index_files()?;
+        let target_files = self.commit_files(target)?;
+
+        // O ref é movido antes de tocar no disco: se outro processo o alterou,
+        // o erro aparece sem que nenhum arquivo tenha sido sobrescrito
+        self.update_head_commit(old_commit.as_deref(), target, &format!("reset: movendo para {}", label))?;
+
+        if mode == ResetMode::Hard {
+            let tracked: BTreeSet<&String> = old_head.keys().chain(old_index.keys()).chain(target_files.keys()).collect();
+            for path in tracked {
+                match target_files.get(path) {
+                    Some(hash) if self.working_hash(path).as_ref() != Some(hash) => self.write_working_file(path, hash)?,
+                    Some(_) => {}
+                    None => self.remove_working_file(path)?,
+                }
+            }
+        }
+
+        match mode {
+            ResetMode::Soft => self.stage_files(&diff_engine, lock, &old_index, None),
+            ResetMode::Mixed | ResetMode::Hard => {
+                diff_engine.clear_staging_area(lock)?;
+                self.clear_merge_state()?;
+                self.clear_pick_state()
+            }
+        }
+    }
+
+    /// Volta caminhos do index para a versão de um commit (padrão: HEAD),
+    /// sem tocar no working tree (`cogit reset <caminho>` tira do staging)
+    pub fn reset_paths(&self, source: Option<&str>, paths: &[String]) -> Result<Vec<String>, CogitError> {
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        let index_files = self.index_files()?;
+        let source_files = match source {
+            Some(commit_hash) => self.commit_files(commit_hash)?,
+            None => self.head_files()?,
+        };
+        let staged = diff_engine.load_staging_area()?;
+
+        let mut selected: Vec<String> = index_files
+            .keys()
+            .chain(source_files.keys())
+            .chain(staged.entries.keys())
+            .filter(|file| paths.iter().any(|path| path_matches(file, path)))
+            .cloned()
+            .collect();
+        selected.sort();
+        selected.dedup();
+
+        if let Some(path) = paths
+            .iter()
+            .find(|path| !selected.iter().any(|file| path_matches(file, path)))
+        {
+            return Err(CogitError::IoError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("'{}' não é rastreado nem está no staging area", path),
+            )));
+        }
+
+        let mut target = index_files;
+        for path in &selected {
+            match source_files.get(path) {
+                Some(hash) => target.insert(path.clone(), hash.clone()),
+                None => target.remove(path),
+            };
+        }
+        self.stage_files(&diff_engine, lock, &target, Some(&selected))?;
+
+        Ok(selected)
+    }
+
+    /// Reescreve o staging area para que o index fique igual a `target`
+    ///
+    /// Com `only`, apenas as entradas desses caminhos são recalculadas. O lock
+    /// do index deve ter sido adquirido antes da leitura que produziu `target`.
+    fn stage_files(
+        &self,
+        diff_engine: &DiffEngine,
+        lock: LockFile,
+        target: &BTreeMap<String, String>,
+        only: Option<&[String]>,
+    ) -> Result<(), CogitError> {
+        let head_files = self.head_files()?;
+        let mut staging_area = match only {
+            Some(_) => diff_engine.load_staging_area()?,
+            None => StagingArea::empty(),
+        };
+
+        let paths: BTreeSet<&String> = match only {
+            Some(paths) => paths.iter().collect(),
+            None => head_files.keys().chain(target.keys()).collect(),
+        };
+
+        for path in paths {
+            let entry = match (head_files.get(path), target.get(path)) {
+                (head, wanted) if head == wanted => None,
+                (_, Some(hash)) => Some(StagingEntry::blob(path, hash, self.load_object(hash)?.len() as u64)),
+                (Some(_), None) => Some(StagingEntry::deletion(path)),
+                (None, None) => None,
+            };
+
+            match entry {
+                Some(entry) => staging_area.entries.insert(path.clone(), entry),
+                None => staging_area.entries.remove(path),
+            };
+        }
+
+        staging_area.last_updated = Utc::now();
+        diff_engine.write_staging_area(lock, &staging_area)
+    }
+}
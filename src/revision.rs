@@ -0,0 +1,299 @@
+use std::fs;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::refs::is_full_hash;
+
+/// Tamanho mínimo de um prefixo de hash aceito como revisão
+const MIN_PREFIX_LEN: usize = 4;
+
+impl CogitRepository {
+    /// Resolve uma revisão para o hash completo de um commit
+    ///
+    /// Aceita `HEAD` (ou `@`), nomes de branches e tags, referências completas
+    /// (`refs/heads/main`) ou relativas a refs/ (`stash`), entradas do reflog (`HEAD@{2}`), hashes completos ou
+    /// prefixos únicos (tags anotadas são seguidas até o commit marcado), seguidos de
+    /// qualquer combinação de `~n` (n-ésimo ancestral) e `^n` (n-ésimo pai).
+    pub fn resolve_revision(&self, spec: &str) -> Result<String, CogitError> {
+        let spec = spec.trim();
+        let base_end = spec.find(['~', '^']).unwrap_or(spec.len());
+        let (base, mut suffixes) = spec.split_at(base_end);
+
+        let mut hash = self.resolve_revision_base(spec, base)?;
+
+        while let Some(operator) = suffixes.chars().next() {
+            let rest = &suffixes[operator.len_utf8()..];
+            let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let count = match &rest[..digits_end] {
+                "" => 1,
+                digits => digits
+                    .parse::<usize>()
+                    .map_err(|_| CogitError::UnknownRevision(spec.to_string()))?,
+            };
+            suffixes = &rest[digits_end..];
+
+            hash = match operator {
+                '~' => self.nth_ancestor(spec, &hash, count)?,
+                '^' => self.nth_parent(spec, &hash, count)?,
+                _ => return Err(CogitError::UnknownRevision(spec.to_string())),
+            };
+        }
+
+        Ok(hash)
+    }
+
+    /// Resolve a parte da revisão antes dos operadores `~`/`^`
+    fn resolve_revision_base(&self, spec: &str, base: &str) -> Result<String, CogitError> {
+        let unknown = || CogitError::UnknownRevision(spec.to_string());
+
+        if base.is_empty() {
+            return Err(unknown());
+        }
+
+        // Entrada do reflog: HEAD@{2}, main@{1}, @{1} (branch atual)
+        if let Some((name, selector)) = base.strip_suffix('}').and_then(|rest| rest.rsplit_once("@{")) {
+            let position: usize = selector.parse().map_err(|_| unknown())?;
+            let ref_name = self.reflog_name(name)?.ok_or_else(unknown)?;
+            return self
+                .read_reflog(&ref_name)?
+                .into_iter()
+                .nth(position)
+                .map(|entry| entry.new)
+                .ok_or_else(unknown);
+        }
+
+        if base == "HEAD" || base == "@" {
+            return self.head_commit()?.ok_or_else(unknown);
+        }
+
+        for candidate in [
+            base.to_string(),
+            format!("refs/heads/{}", base),
+            format!("refs/tags/{}", base),
+            format!("refs/{}", base),
+        ] {
+            if !candidate.starts_with("refs/") || candidate.contains("..") {
+                continue;
+            }
+            let path = self.cogit_dir().join(&candidate);
+            if path.is_file() {
+                return self.peel_to_commit(fs::read_to_string(path)?.trim());
+            }
+        }
+
+        if base.len() >= MIN_PREFIX_LEN && base.chars().all(|c| c.is_ascii_hexdigit()) {
+            let prefix = base.to_ascii_lowercase();
+            if is_full_hash(&prefix) {
+                if let Ok(commit) = self.peel_to_commit(&prefix) {
+                    return Ok(commit);
+                }
+            }
+
+            let mut candidates: Vec<String> = self
+                .find_objects_by_prefix(&prefix)?
+                .into_iter()
+                .filter(|hash| self.peel_to_commit(hash).is_ok())
+                .collect();
+
+            return match candidates.len() {
+                0 => Err(unknown()),
+                1 => self.peel_to_commit(&candidates.remove(0)),
+                _ => Err(CogitError::AmbiguousRevision(spec.to_string(), candidates)),
+            };
+        }
+
+        Err(unknown())
+    }
+
+    /// Lista os objetos (soltos ou em packs) cujo hash começa com o prefixo dado
+    pub fn find_objects_by_prefix(&self, prefix: &str) -> Result<Vec<String>, CogitError> {
+        let mut matches = Vec::new();
+        if prefix.len() < 2 {
+            return Ok(matches);
+        }
+
+        let object_dir = self.cogit_dir().join("objects").join(&prefix[..2]);
+        if object_dir.is_dir() {
+            for entry in fs::read_dir(object_dir)? {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                let hash = format!("{}{}", &prefix[..2], name);
+                if hash.starts_with(prefix) && is_full_hash(&hash) {
+                    matches.push(hash);
+                }
+            }
+        }
+
+        matches.extend(
+            self.packed_objects()?
+                .into_iter()
+                .filter(|hash| hash.starts_with(prefix)),
+        );
+
+        matches.sort();
+        matches.dedup();
+        Ok(matches)
+    }
+
+    /// Segue o primeiro pai `count` vezes (`rev~n`)
+    fn nth_ancestor(&self, spec: &str, hash: &str, count: usize) -> Result<String, CogitError> {
+        let mut current = hash.to_string();
+        for _ in 0..count {
+            current = self.nth_parent(spec, &current, 1)?;
+        }
+        Ok(current)
+    }
+
+    /// Retorna o n-ésimo pai de um commit (`rev^n`; `rev^0` é o próprio commit)
+    fn nth_parent(&self, spec: &str, hash: &str, n: usize) -> Result<String, CogitError> {
+        if n == 0 {
+            return Ok(hash.to_string());
+        }
+
+        let mut commit = self.load_commit(hash)?;
+        if n > commit.parents.len() {
+            return Err(CogitError::UnknownRevision(spec.to_string()));
+        }
+        Ok(commit.parents.swap_remove(n - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::path::PathBuf;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::config::Signature;
+
+    /// Repositório temporário, apagado ao fim do teste
+    struct TestRepo {
+        dir: PathBuf,
+        repo: CogitRepository,
+    }
+
+    impl TestRepo {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("cogit-revision-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = CogitRepository::init(&dir).unwrap();
+            Self { dir, repo }
+        }
+
+        /// Grava um commit (com árvore vazia) sem mover referências
+        fn commit(&self, message: &str, parents: &[&str]) -> String {
+            let signature = Signature {
+                name: "Teste".to_string(),
+                email: "teste@example.com".to_string(),
+                timestamp: Utc::now(),
+            };
+            let tree = self.repo.create_tree_from_files(&BTreeMap::new()).unwrap();
+            let parents = parents.iter().map(|parent| parent.to_string()).collect();
+            self.repo.write_commit(tree, parents, message, signature.clone(), signature).unwrap()
+        }
+
+        /// Grava um commit sobre o HEAD e avança o branch atual
+        fn commit_on_head(&self, message: &str, extra_parents: &[&str]) -> String {
+            let head = self.repo.head_commit().unwrap();
+            let mut parents: Vec<&str> = head.iter().map(String::as_str).collect();
+            parents.extend_from_slice(extra_parents);
+            let hash = self.commit(message, &parents);
+            self.repo.update_head_commit(head.as_deref(), &hash, &format!("commit: {}", message)).unwrap();
+            hash
+        }
+    }
+
+    impl Drop for TestRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn assert_unknown(result: Result<String, CogitError>) {
+        assert!(matches!(result, Err(CogitError::UnknownRevision(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn resolves_ancestors_and_parents() {
+        let test = TestRepo::new("ancestors");
+        let first = test.commit_on_head("primeiro", &[]);
+        let second = test.commit_on_head("segundo", &[]);
+        let third = test.commit_on_head("terceiro", &[]);
+        let side = test.commit("lateral", &[&first]);
+        let merge = test.commit_on_head("merge", &[&side]);
+        let resolve = |spec: &str| test.repo.resolve_revision(spec);
+
+        assert_eq!(resolve("HEAD").unwrap(), merge);
+        assert_eq!(resolve("main").unwrap(), merge);
+        assert_eq!(resolve("HEAD^0").unwrap(), merge);
+        assert_eq!(resolve("HEAD~").unwrap(), third);
+        assert_eq!(resolve("HEAD^").unwrap(), third);
+        assert_eq!(resolve("HEAD~2").unwrap(), second);
+        assert_eq!(resolve("HEAD^^").unwrap(), second);
+        assert_eq!(resolve("HEAD~3").unwrap(), first);
+        assert_eq!(resolve("main^2").unwrap(), side);
+        assert_eq!(resolve("HEAD^2~1").unwrap(), first);
+        assert_eq!(resolve("@~1^").unwrap(), second);
+
+        assert_unknown(resolve("HEAD^3"));
+        assert_unknown(resolve("HEAD~4"));
+        assert_unknown(resolve("HEAD~x"));
+        assert_unknown(resolve("HEAD~é"));
+        assert_unknown(resolve("HEAD^2é~"));
+        assert_unknown(resolve("nada"));
+    }
+
+    #[test]
+    fn resolves_reflog_entries() {
+        let test = TestRepo::new("reflog");
+        let first = test.commit_on_head("primeiro", &[]);
+        let second = test.commit_on_head("segundo", &[]);
+        let third = test.commit_on_head("terceiro", &[]);
+        let resolve = |spec: &str| test.repo.resolve_revision(spec);
+
+        assert_eq!(resolve("HEAD@{0}").unwrap(), third);
+        assert_eq!(resolve("HEAD@{1}").unwrap(), second);
+        assert_eq!(resolve("main@{2}").unwrap(), first);
+        assert_eq!(resolve("@{1}").unwrap(), second);
+        assert_eq!(resolve("HEAD@{1}~1").unwrap(), first);
+
+        assert_unknown(resolve("HEAD@{3}"));
+        assert_unknown(resolve("HEAD@{x}"));
+    }
+
+    #[test]
+    fn resolves_unique_prefixes_and_rejects_ambiguous_ones() {
+        let test = TestRepo::new("prefix");
+
+        // Cria commits até que dois compartilhem os primeiros caracteres do hash
+        let mut by_prefix: HashMap<String, String> = HashMap::new();
+        let (first, second) = (0..)
+            .find_map(|i| {
+                let hash = test.commit(&format!("commit {}", i), &[]);
+                let prefix = hash[..MIN_PREFIX_LEN].to_string();
+                by_prefix.insert(prefix, hash.clone()).map(|other| (other, hash))
+            })
+            .unwrap();
+
+        let prefix = &first[..MIN_PREFIX_LEN];
+        match test.repo.resolve_revision(prefix) {
+            Err(CogitError::AmbiguousRevision(_, mut candidates)) => {
+                candidates.sort();
+                let mut expected = vec![first.clone(), second.clone()];
+                expected.sort();
+                assert_eq!(candidates, expected);
+            }
+            other => panic!("esperava revisão ambígua, obtive {:?}", other),
+        }
+
+        let unique = (MIN_PREFIX_LEN..first.len())
+            .map(|len| &first[..len])
+            .find(|prefix| !second.starts_with(prefix))
+            .unwrap();
+        assert_eq!(test.repo.resolve_revision(unique).unwrap(), first);
+        assert_eq!(test.repo.resolve_revision(&unique.to_ascii_uppercase()).unwrap(), first);
+        assert_eq!(test.repo.resolve_revision(&second).unwrap(), second);
+        assert_unknown(test.repo.resolve_revision(&first[..MIN_PREFIX_LEN - 1]));
+    }
+}
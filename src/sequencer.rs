@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::DiffEngine;
+use crate::lockfile::write_atomic;
+
+/// Operação que reaplica as mudanças de um único commit sobre o HEAD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickKind {
+    CherryPick,  // Aplica as mudanças do commit em relação ao seu pai
+    Revert,      // Aplica o inverso dessas mudanças
+}
+
+impl PickKind {
+    /// Nome da operação, como nos comandos e nas mensagens
+    pub fn name(self) -> &'static str {
+        match self {
+            PickKind::CherryPick => "cherry-pick",
+            PickKind::Revert => "revert",
+        }
+    }
+
+    /// Arquivo que guarda o commit original enquanto a operação está parada
+    /// (.cogit/CHERRY_PICK_HEAD ou .cogit/REVERT_HEAD)
+    pub fn state_file(self) -> &'static str {
+        match self {
+            PickKind::CherryPick => "CHERRY_PICK_HEAD",
+            PickKind::Revert => "REVERT_HEAD",
+        }
+    }
+}
+
+/// Resultado de `cogit cherry-pick` / `cogit revert`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickOutcome {
+    Committed(String),       // Novo commit criado
+    Empty,                   // As mudanças já estavam no HEAD: nada a commitar
+    Conflicts(Vec<String>),  // Operação parada: arquivos com conflito
+}
+
+impl CogitRepository {
+    /// Aplica sobre o HEAD as mudanças de um commit (cherry-pick) ou o inverso
+    /// delas (revert), com o mesmo merge de três vias do `cogit merge`
+    ///
+    /// No cherry-pick a base é o pai do commit e o outro lado o próprio commit;
+    /// no revert os dois se invertem. Sem conflitos, um novo commit é criado
+    /// referenciando o original na mensagem (o cherry-pick mantém o autor
+    /// original). Com conflitos, a operação para até `continue_pick` ou `abort_pick`.
+    pub fn pick(&self, kind: PickKind, commit_hash: &str) -> Result<PickOutcome, CogitError> {
+        if self.merge_in_progress() {
+            return Err(CogitError::OperationInProgress("merge".to_string()));
+        }
+        if let Some(current) = self.pick_in_progress() {
+            return Err(CogitError::OperationInProgress(current.name().to_string()));
+        }
+
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let staged: Vec<String> = diff_engine.load_staging_area()?.entries.into_keys().collect();
+        if !staged.is_empty() {
+            return Err(CogitError::UncommittedChanges(staged));
+        }
+        let head = self
+            .head_commit()?
+            .ok_or_else(|| CogitError::UnknownRevision("HEAD".to_string()))?;
+
+        let commit = self.load_commit(commit_hash)?;
+        if commit.parents.len() > 1 {
+            return Err(CogitError::MergeCommitNotSupported(commit_hash.to_string()));
+        }
+        let parent_files = match commit.parents.first() {
+            Some(parent) => self.commit_files(parent)?,
+            None => BTreeMap::new(),
+        };
+        let commit_files = self.flatten_tree(&commit.tree_hash)?;
+        let (base, theirs) = match kind {
+            PickKind::CherryPick => (parent_files, commit_files),
+            PickKind::Revert => (commit_files, parent_files),
+        };
+
+        let label = format!("{} ({})", &commit_hash[..7], commit.message.lines().next().unwrap_or_default());
+        let result = self.merge_trees(&base, &self.head_files()?, &theirs, &label)?;
+
+        if !result.conflicts.is_empty() {
+            write_atomic(&self.cogit_dir().join(kind.state_file()), format!("{}\n", commit_hash).as_bytes())?;
+            return Ok(PickOutcome::Conflicts(result.conflicts));
+        }
+        if result.updated.is_empty() {
+            return Ok(PickOutcome::Empty);
+        }
+
+        self.finish_pick(kind, commit_hash, &head).map(PickOutcome::Committed)
+    }
+
+    /// Conclui um cherry-pick ou revert parado, commitando o que está no index
+    /// (os conflitos já devem ter sido resolvidos com `cogit add`)
+    pub fn continue_pick(&self, kind: PickKind) -> Result<String, CogitError> {
+        let commit_hash = self
+            .pick_head(kind)?
+            .ok_or_else(|| CogitError::NoOperationInProgress(kind.name().to_string()))?;
+        let head = self
+            .head_commit()?
+            .ok_or_else(|| CogitError::UnknownRevision("HEAD".to_string()))?;
+
+        self.finish_pick(kind, &commit_hash, &head)
+    }
+
+    /// Cancela um cherry-pick ou revert parado, devolvendo o working tree e o
+    /// index ao estado do HEAD
+    pub fn abort_pick(&self, kind: PickKind) -> Result<(), CogitError> {
+        if self.pick_head(kind)?.is_none() {
+            return Err(CogitError::NoOperationInProgress(kind.name().to_string()));
+        }
+
+        self.discard_staged_changes()?;
+        self.clear_pick_state()
+    }
+
+    /// Cherry-pick ou revert parado esperando `--continue` ou `--abort`
+    pub fn pick_in_progress(&self) -> Option<PickKind> {
+        [PickKind::CherryPick, PickKind::Revert]
+            .into_iter()
+            .find(|kind| self.cogit_dir().join(kind.state_file()).is_file())
+    }
+
+    /// Remove o estado de um cherry-pick ou revert parado
+    pub fn clear_pick_state(&self) -> Result<(), CogitError> {
+        for kind in [PickKind::CherryPick, PickKind::Revert] {
+            let path = self.cogit_dir().join(kind.state_file());
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Commit original da operação parada, se houver
+    pub fn pick_head(&self, kind: PickKind) -> Result<Option<String>, CogitError> {
+        let path = self.cogit_dir().join(kind.state_file());
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?.trim().to_string()))
+    }
+
+    /// Grava o commit com o conteúdo do index sobre o HEAD e avança o branch
+    fn finish_pick(&self, kind: PickKind, commit_hash: &str, head: &str) -> Result<String, CogitError> {
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        let staging_area = diff_engine.load_staging_area()?;
+        let unmerged = staging_area.conflicted_paths();
+        if !unmerged.is_empty() {
+            return Err(CogitError::UnmergedPaths(unmerged));
+        }
+
+        let original = self.load_commit(commit_hash)?;
+        let subject = original.message.lines().next().unwrap_or_default();
+        let (mut author, committer) = self.commit_signatures()?;
+        let message = match kind {
+            PickKind::CherryPick => {
+                // O autor (e a data) do commit original são preservados
+                if let Some(original_author) = original.author.clone() {
+                    author = original_author;
+                }
+                format!("{}\n\n(cherry-pick do commit {})", original.message.trim_end(), commit_hash)
+            }
+            PickKind::Revert => format!("Revert \"{}\"\n\nIsto reverte o commit {}.", subject, commit_hash),
+        };
+
+        let tree_hash = self.create_tree(&staging_area)?;
+        let hash = self.write_commit(tree_hash, vec![head.to_string()], &message, author, committer)?;
+
+        self.update_head_commit(Some(head), &hash, &format!("{}: {}", kind.name(), message.lines().next().unwrap_or_default()))?;
+        diff_engine.clear_staging_area(lock)?;
+        self.clear_pick_state()?;
+        Ok(hash)
+    }
+}
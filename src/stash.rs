@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::DiffEngine;
+use crate::lockfile::LockFile;
+use crate::merge::TreeMerge;
+use crate::object::ObjectType;
+
+/// Referência do stash mais recente; os anteriores ficam no seu reflog
+pub const STASH_REF: &str = "refs/stash";
+
+/// Entrada da pilha de stash
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+    pub position: usize,  // stash@{n}, 0 = o mais recente
+    pub hash: String,
+    pub message: String,
+}
+
+/// Interpreta a referência de um stash: `stash@{n}` ou apenas `n`
+pub fn parse_stash_position(spec: &str) -> Result<usize, CogitError> {
+    let digits = spec
+        .strip_prefix("stash@{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .unwrap_or(spec);
+    digits
+        .parse()
+        .map_err(|_| CogitError::UnknownRevision(spec.to_string()))
+}
+
+impl CogitRepository {
+    /// Guarda as mudanças locais em arquivos rastreados e limpa o working tree
+    ///
+    /// O stash é um commit cujo primeiro pai é o HEAD e o segundo um commit com
+    /// o conteúdo do index; a árvore do stash é a do working tree. Arquivos não
+    /// rastreados não são guardados, e conflitos não resolvidos impedem o stash.
+    /// Retorna None se não havia nada a guardar.
+    pub fn stash_push(&self, message: Option<&str>) -> Result<Option<String>, CogitError> {
+        if self.merge_in_progress() {
+            return Err(CogitError::OperationInProgress("merge".to_string()));
+        }
+        if let Some(kind) = self.pick_in_progress() {
+            return Err(CogitError::OperationInProgress(kind.name().to_string()));
+        }
+        // O index fica travado até ser limpo, para que nenhum `cogit add` se perca entre os dois
+        let diff_engine = DiffEngine::new(self.cogit_dir().to_path_buf());
+        let lock = diff_engine.lock_staging_area()?;
+        let unmerged = diff_engine.load_staging_area()?.conflicted_paths();
+        if !unmerged.is_empty() {
+            return Err(CogitError::UnmergedPaths(unmerged));
+        }
+
+        let head = self
+            .head_commit()?
+            .ok_or_else(|| CogitError::UnknownRevision("HEAD".to_string()))?;
+
+        let head_files = self.head_files()?;
+        let index_files = self.index_files()?;
+
+        let mut working_files = BTreeMap::new();
+        for path in head_files.keys().chain(index_files.keys()) {
+            if let Ok(content) = fs::read(self.root_path().join(path)) {
+                working_files.insert(path.clone(), self.store_object(ObjectType::Blob, &content)?);
+            }
+        }
+
+        if index_files == head_files && working_files == index_files {
+            return Ok(None);
+        }
+
+        let head_commit = self.load_commit(&head)?;
+        let location = self.head_description()?;
+        let message = match message {
+            Some(message) => format!("Em {}: {}", location, message),
+            None => format!(
+                "WIP em {}: {} {}",
+                location,
+                &head[..7],
+                head_commit.message.lines().next().unwrap_or_default()
+            ),
+        };
+
+        let (author, committer) = self.commit_signatures()?;
+        let index_tree = self.create_tree_from_files(&index_files)?;
+        let index_commit = self.write_commit(
+            index_tree,
+            vec![head.clone()],
+            &format!("index em {}", location),
+            author.clone(),
+            committer.clone(),
+        )?;
+        let working_tree = self.create_tree_from_files(&working_files)?;
+        let stash = self.write_commit(working_tree, vec![head.clone(), index_commit], &message, author, committer)?;
+
+        self.write_stash_ref(&stash, &message)?;
+
+        // Devolve o working tree e o index ao HEAD sem mover referências
+        for path in head_files.keys().chain(index_files.keys()) {
+            if working_files.get(path) != head_files.get(path) {
+                match head_files.get(path) {
+                    Some(hash) => self.write_working_file(path, hash)?,
+                    None => self.remove_working_file(path)?,
+                }
+            }
+        }
+        diff_engine.clear_staging_area(lock)?;
+        Ok(Some(stash))
+    }
+
+    /// Lista a pilha de stash, do mais recente para o mais antigo
+    pub fn stash_list(&self) -> Result<Vec<StashEntry>, CogitError> {
+        Ok(self
+            .read_reflog(STASH_REF)?
+            .into_iter()
+            .enumerate()
+            .map(|(position, entry)| StashEntry { position, hash: entry.new, message: entry.message })
+            .collect())
+    }
+
+    /// Reaplica um stash sobre o HEAD atual com um merge de três vias
+    ///
+    /// A base é o commit em que o stash foi criado. Arquivos com mudanças locais
+    /// que o stash precisaria alterar fazem a operação ser recusada; conflitos
+    /// ficam marcados nos arquivos e no index, e o stash é mantido. Sem
+    /// conflitos, só os caminhos que estavam staged voltam ao staging area.
+    pub fn stash_apply(&self, position: usize) -> Result<TreeMerge, CogitError> {
+        let entry = self.stash_entry(position)?;
+        let stash = self.load_commit(&entry.hash)?;
+        let (Some(base), Some(index)) = (stash.parents.first(), stash.parents.get(1)) else {
+            return Err(CogitError::CorruptObject(entry.hash, "stash sem os commits de base e de index".to_string()));
+        };
+
+        let base_files = self.commit_files(base)?;
+        let index_files = self.commit_files(index)?;
+        let result = self.merge_trees(
+            &base_files,
+            &self.index_files()?,
+            &self.flatten_tree(&stash.tree_hash)?,
+            &format!("stash@{{{}}}", position),
+        )?;
+
+        if result.conflicts.is_empty() {
+            let unstaged: Vec<String> = result
+                .updated
+                .iter()
+                .filter(|path| index_files.get(*path) == base_files.get(*path))
+                .cloned()
+                .collect();
+            if !unstaged.is_empty() {
+                self.reset_paths(None, &unstaged)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Remove um stash da pilha, devolvendo a entrada removida
+    pub fn stash_drop(&self, position: usize) -> Result<StashEntry, CogitError> {
+        // O lock de refs/stash cobre o reflog e a referência, para que um
+        // `stash push` simultâneo não se perca entre as duas escritas
+        let lock = LockFile::acquire(&self.cogit_dir().join(STASH_REF))?;
+        let entry = self.stash_entry(position)?;
+        self.drop_reflog_entry(STASH_REF, position)?;
+
+        // A referência sempre aponta para o topo da pilha
+        if position == 0 {
+            let top = self.read_reflog(STASH_REF)?.into_iter().next().map(|top| top.new);
+            self.update_stash_ref(lock, top.as_deref())?;
+        }
+        Ok(entry)
+    }
+
+    fn stash_entry(&self, position: usize) -> Result<StashEntry, CogitError> {
+        self.stash_list()?
+            .into_iter()
+            .nth(position)
+            .ok_or_else(|| CogitError::UnknownRevision(format!("stash@{{{}}}", position)))
+    }
+
+    /// Aponta refs/stash para um novo stash, registrando no reflog
+    fn write_stash_ref(&self, hash: &str, message: &str) -> Result<(), CogitError> {
+        let path = self.cogit_dir().join(STASH_REF);
+        let lock = LockFile::acquire(&path)?;
+        let old = match path.is_file() {
+            true => Some(fs::read_to_string(&path)?.trim().to_string()),
+            false => None,
+        };
+        self.append_reflog(STASH_REF, old.as_deref(), hash, message)?;
+        self.update_stash_ref(lock, Some(hash))
+    }
+
+    /// Grava refs/stash sob o lock já adquirido, ou apaga a referência e o
+    /// reflog quando a pilha fica vazia
+    fn update_stash_ref(&self, lock: LockFile, hash: Option<&str>) -> Result<(), CogitError> {
+        match hash {
+            Some(hash) => lock.commit(format!("{}\n", hash).as_bytes()),
+            None => {
+                let path = self.cogit_dir().join(STASH_REF);
+                if path.is_file() {
+                    fs::remove_file(&path)?;
+                }
+                self.delete_reflog(STASH_REF)
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::{SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cogit::{canonical_object, canonical_signature, CogitError, CogitRepository};
+use crate::config::{Signature, SignatureRole};
+use crate::ignore::glob_match;
+use crate::lockfile::LockFile;
+use crate::object::ObjectType;
+use crate::refs::{remove_empty_parents, validate_ref_name};
+
+/// Prefixo das referências de tags
+pub const TAGS_PREFIX: &str = "refs/tags/";
+
+/// Limite de tags encadeadas (tag de tag) seguidas ao buscar o commit marcado
+const MAX_PEEL_DEPTH: usize = 16;
+
+/// Tag anotada, gravada como objeto no object store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub object: String,           // Hash do objeto marcado
+    #[serde(rename = "type")]
+    pub object_type: ObjectType,  // Tipo do objeto marcado
+    pub tag: String,              // Nome da tag no momento da criação
+    pub tagger: Signature,
+    pub message: String,
+}
+
+impl Tag {
+    /// Codificação canônica da tag (mesmas regras da de um commit)
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, CogitError> {
+        Ok(canonical_object(&[
+            ("message", serde_json::to_string(&self.message)?),
+            ("object", serde_json::to_string(&self.object)?),
+            ("tag", serde_json::to_string(&self.tag)?),
+            ("tagger", canonical_signature(&self.tagger)?),
+            ("type", serde_json::to_string(&self.object_type)?),
+        ])
+        .into_bytes())
+    }
+}
+
+/// Tag listada: nome, valor da referência e, se anotada, o objeto da tag
+#[derive(Debug, Clone)]
+pub struct TagRef {
+    pub name: String,
+    pub target: String,
+    pub annotation: Option<Tag>,
+}
+
+impl CogitRepository {
+    /// Caminho do arquivo de uma tag (refs/tags/<nome>), recusando nomes
+    /// inválidos (como `..`) que levariam para fora de refs/tags
+    fn tag_path(&self, name: &str) -> Result<PathBuf, CogitError> {
+        validate_ref_name(name)?;
+        Ok(self.cogit_dir().join(TAGS_PREFIX).join(name))
+    }
+
+    /// Cria uma tag para um commit
+    ///
+    /// Sem mensagem, a tag é leve: a referência aponta direto para o commit.
+    /// Com mensagem, um objeto de tag (com quem marcou e quando) é gravado e a
+    /// referência aponta para ele. Retorna o hash gravado na referência.
+    pub fn create_tag(&self, name: &str, commit_hash: &str, message: Option<&str>, force: bool) -> Result<String, CogitError> {
+        let path = self.tag_path(name)?;
+        let lock = LockFile::acquire(&path)?;
+        if !force && path.is_file() {
+            return Err(CogitError::TagExists(name.to_string()));
+        }
+
+        let target = match message {
+            Some(message) => {
+                let mut tagger = self.signature(SignatureRole::Committer, Utc::now())?;
+                tagger.timestamp = tagger.timestamp.trunc_subsecs(0);
+                let tag = Tag {
+                    object: commit_hash.to_string(),
+                    object_type: ObjectType::Commit,
+                    tag: name.to_string(),
+                    tagger,
+                    message: message.to_string(),
+                };
+                self.store_object(ObjectType::Tag, &tag.canonical_bytes()?)?
+            }
+            None => commit_hash.to_string(),
+        };
+
+        lock.commit(format!("{}\n", target).as_bytes())?;
+        Ok(target)
+    }
+
+    /// Remove uma tag (o objeto de uma tag anotada fica para o gc)
+    pub fn delete_tag(&self, name: &str) -> Result<(), CogitError> {
+        let path = self.tag_path(name)?;
+        if !path.is_file() {
+            return Err(CogitError::TagNotFound(name.to_string()));
+        }
+
+        fs::remove_file(&path)?;
+        remove_empty_parents(&path, &self.cogit_dir().join(TAGS_PREFIX));
+        Ok(())
+    }
+
+    /// Lista as tags em ordem, opcionalmente filtradas por um glob (`v1.*`)
+    pub fn list_tags(&self, pattern: Option<&str>) -> Result<Vec<TagRef>, CogitError> {
+        let mut tags = Vec::new();
+        for (full_name, target) in self.list_refs()? {
+            let Some(name) = full_name.strip_prefix(TAGS_PREFIX) else {
+                continue;
+            };
+            if pattern.is_some_and(|pattern| !glob_match(pattern, name)) {
+                continue;
+            }
+
+            let annotation = match self.read_object(&target) {
+                Ok((ObjectType::Tag, _)) => Some(self.load_tag(&target)?),
+                _ => None,
+            };
+            tags.push(TagRef { name: name.to_string(), target, annotation });
+        }
+        Ok(tags)
+    }
+
+    /// Carrega e desserializa um objeto de tag anotada
+    pub fn load_tag(&self, hash: &str) -> Result<Tag, CogitError> {
+        let tag_data = self.load_typed_object(hash, ObjectType::Tag)?;
+        Ok(serde_json::from_slice(&tag_data)?)
+    }
+
+    /// Segue tags anotadas até o commit marcado (um commit é devolvido como está)
+    pub fn peel_to_commit(&self, hash: &str) -> Result<String, CogitError> {
+        let mut current = hash.to_string();
+        for _ in 0..MAX_PEEL_DEPTH {
+            match self.read_object(&current)?.0 {
+                ObjectType::Tag => current = self.load_tag(&current)?.object,
+                ObjectType::Commit => return Ok(current),
+                _ => break,
+            }
+        }
+        Err(CogitError::UnexpectedObjectType(current, ObjectType::Commit))
+    }
+}
@@ -0,0 +1,257 @@
+use chrono::Utc;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use crate::cogit::{CogitError, CogitRepository};
+use crate::diff::{path_matches, DiffEngine, StagingEntry};
+
+impl CogitRepository {
+    /// Arquivos de um commit, achatados em caminho relativo -> hash do blob
+    pub fn commit_files(&self, commit_hash: &str) -> Result<BTreeMap<String, String>, CogitError> {
+        let commit = self.load_commit(commit_hash)?;
+        self.flatten_tree(&commit.tree_hash)
+    }
+
+    /// Visão do index: arquivos do HEAD com as entradas staged aplicadas por cima
+    pub fn index_files(&self) -> Result<BTreeMap<String, String>, CogitError> {
+        let mut files = self.head_files()?;
+        let staging_area = self.diff_engine().load_staging_area()?;
+        for (path, entry) in staging_area.entries {
+            if entry.deleted {
+                files.remove(&path);
+            } else {
+                files.insert(path, entry.content_hash);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Troca para um branch, atualizando o working tree para o seu último commit
+    pub fn switch_branch(&self, name: &str, force: bool) -> Result<(), CogitError> {
+        let target = self
+            .read_branch(name)?
+            .ok_or_else(|| CogitError::BranchNotFound(name.to_string()))?;
+
+        let reason = format!("checkout: de {} para {}", self.head_description()?, name);
+        self.checkout_tree(&self.commit_files(&target)?, force)?;
+        self.set_head_branch(name, &reason)
+    }
+
+    /// Destaca o HEAD em um commit, atualizando o working tree
+    pub fn checkout_detached(&self, commit_hash: &str, force: bool) -> Result<(), CogitError> {
+        let reason = format!("checkout: de {} para {}", self.head_description()?, &commit_hash[..7]);
+        self.checkout_tree(&self.commit_files(commit_hash)?, force)?;
+        self.set_head_detached(commit_hash, &reason)
+    }
+
+    /// Atualiza o working tree e o index do HEAD atual para o conjunto de arquivos alvo
+    ///
+    /// Apenas os caminhos que diferem entre o HEAD e o alvo são tocados; se algum
+    /// deles tiver mudanças não commitadas (ou um arquivo não rastreado seria
+    /// sobrescrito), a operação é recusada a menos que `force` seja usado.
+    pub fn checkout_tree(&self, target: &BTreeMap<String, String>, force: bool) -> Result<(), CogitError> {
+        let current = self.head_files()?;
+        let diff_engine = self.diff_engine();
+        let lock = diff_engine.lock_staging_area()?;
+        let mut staging_area = diff_engine.load_staging_area()?;
+
+        let changed: BTreeSet<&String> = current
+            .keys()
+            .chain(target.keys())
+            .filter(|path| current.get(*path) != target.get(*path))
+            .collect();
+
+        if !force {
+            let conflicts: Vec<String> = changed
+                .iter()
+                .filter(|path| {
+                    let working = self.working_hash(path);
+                    staging_area.entries.contains_key(path.as_str())
+                        || (working.as_ref() != current.get(path.as_str())
+                            && working.as_ref() != target.get(path.as_str()))
+                })
+                .map(|path| path.to_string())
+                .collect();
+
+            if !conflicts.is_empty() {
+                return Err(CogitError::UncommittedChanges(conflicts));
+            }
+        }
+
+        for path in &changed {
+            match target.get(path.as_str()) {
+                Some(hash) => self.write_working_file(path, hash)?,
+                None => self.remove_working_file(path)?,
+            }
+        }
+
+        if force {
+            staging_area.entries.clear();
+        } else {
+            staging_area.entries.retain(|path, _| !changed.contains(path));
+        }
+        diff_engine.write_staging_area(lock, &staging_area)
+    }
+
+    /// Restaura caminhos do working tree a partir de um commit (ou do index, se `source` for None)
+    ///
+    /// Cada caminho pode ser um arquivo ou um diretório. Arquivos com mudanças
+    /// ainda não registradas no index só são sobrescritos com `force`.
+    pub fn restore_paths(
+        &self,
+        paths: &[String],
+        source: Option<&str>,
+        force: bool,
+    ) -> Result<Vec<String>, CogitError> {
+        let index_files = self.index_files()?;
+        let source_files = match source {
+            Some(commit_hash) => self.commit_files(commit_hash)?,
+            None => index_files.clone(),
+        };
+
+        let mut selected = Vec::new();
+        for path in paths {
+            let matches: Vec<&String> = source_files
+                .keys()
+                .filter(|file| path_matches(file, path))
+                .collect();
+
+            if matches.is_empty() {
+                return Err(CogitError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("'{}' não existe na origem da restauração", path),
+                )));
+            }
+            selected.extend(matches);
+        }
+        selected.sort();
+        selected.dedup();
+
+        if !force {
+            let conflicts: Vec<String> = selected
+                .iter()
+                .filter(|path| {
+                    let working = self.working_hash(path);
+                    working.is_some()
+                        && working.as_ref() != index_files.get(path.as_str())
+                        && working.as_ref() != source_files.get(path.as_str())
+                })
+                .map(|path| path.to_string())
+                .collect();
+
+            if !conflicts.is_empty() {
+                return Err(CogitError::UncommittedChanges(conflicts));
+            }
+        }
+
+        for path in &selected {
+            self.write_working_file(path, &source_files[path.as_str()])?;
+        }
+
+        Ok(selected.into_iter().cloned().collect())
+    }
+
+    /// Remove arquivos rastreados (do disco, a menos que `cached`) e registra a remoção
+    ///
+    /// Arquivos cujo conteúdo difere do index só são removidos com `force`.
+    pub fn remove_paths(&self, paths: &[String], cached: bool, force: bool) -> Result<Vec<String>, CogitError> {
+        let diff_engine = self.diff_engine();
+        let lock = diff_engine.lock_staging_area()?;
+        let index_files = self.index_files()?;
+
+        let mut selected = Vec::new();
+        for path in paths {
+            let matches: Vec<&String> = index_files
+                .keys()
+                .filter(|file| path_matches(file, path))
+                .collect();
+
+            if matches.is_empty() {
+                return Err(CogitError::IoError(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("'{}' não é rastreado", path),
+                )));
+            }
+            selected.extend(matches);
+        }
+        selected.sort();
+        selected.dedup();
+
+        if !force && !cached {
+            let conflicts: Vec<String> = selected
+                .iter()
+                .filter(|path| {
+                    let working = self.working_hash(path);
+                    working.is_some() && working.as_ref() != index_files.get(path.as_str())
+                })
+                .map(|path| path.to_string())
+                .collect();
+
+            if !conflicts.is_empty() {
+                return Err(CogitError::UncommittedChanges(conflicts));
+            }
+        }
+
+        let head_files = self.head_files()?;
+        let mut staging_area = diff_engine.load_staging_area()?;
+        for path in &selected {
+            if !cached {
+                self.remove_working_file(path)?;
+            }
+            // Arquivo só adicionado no index: basta esquecer a entrada
+            if head_files.contains_key(path.as_str()) {
+                staging_area.entries.insert(path.to_string(), StagingEntry::deletion(path));
+            } else {
+                staging_area.entries.remove(path.as_str());
+            }
+        }
+        staging_area.last_updated = Utc::now();
+        diff_engine.write_staging_area(lock, &staging_area)?;
+
+        Ok(selected.into_iter().cloned().collect())
+    }
+
+    /// Motor de diff/staging deste repositório
+    fn diff_engine(&self) -> DiffEngine {
+        DiffEngine::new(self.cogit_dir().to_path_buf())
+    }
+
+    /// Hash do conteúdo atual de um arquivo no working tree (None se não existe)
+    pub fn working_hash(&self, path: &str) -> Option<String> {
+        fs::read(self.root_path().join(path))
+            .ok()
+            .and_then(|content| self.working_blob_hash(&content).ok())
+    }
+
+    /// Materializa um blob do object store no working tree
+    pub fn write_working_file(&self, path: &str, hash: &str) -> Result<(), CogitError> {
+        self.write_working_content(path, &self.load_object(hash)?)
+    }
+
+    /// Grava um conteúdo arbitrário no working tree, criando os diretórios necessários
+    pub fn write_working_content(&self, path: &str, content: &[u8]) -> Result<(), CogitError> {
+        let full_path = self.root_path().join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(full_path, content)?;
+        Ok(())
+    }
+
+    /// Remove um arquivo do working tree e os diretórios que ficarem vazios
+    pub fn remove_working_file(&self, path: &str) -> Result<(), CogitError> {
+        let full_path = self.root_path().join(path);
+        if full_path.is_file() {
+            fs::remove_file(&full_path)?;
+        }
+
+        let mut current = full_path.parent();
+        while let Some(dir) = current {
+            if dir == self.root_path() || fs::remove_dir(dir).is_err() {
+                break;
+            }
+            current = dir.parent();
+        }
+        Ok(())
+    }
+}